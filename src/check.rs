@@ -0,0 +1,474 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::collections::{HashMap, HashSet};
+
+    use crate::{
+        Stm, Decl, Bound,
+        Expr, Val, Op, CmpTo,
+        SyntaxContext,
+        InterpreterErr,
+        Primitive, PrimitiveId,
+        Symbol, Host,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// A pass that infers register types and reports their misuses before anything runs.
+    pub struct TypeChecker<'a> {
+        registers: HashMap<Symbol, PrimitiveId>,
+        procs:     HashMap<Symbol, (&'a [Symbol], &'a Decl)>,
+        hosts:     &'a HashMap<Symbol, Host>,
+        summaries: HashMap<Symbol, ProcSummary>,
+        active:    HashSet<Symbol>,
+        untyped:   HashSet<Symbol>,
+        errors:    Vec<InterpreterErr>,
+    } // struct ..
+
+
+    /// The output type and the registers allocated by an already checked procedure.
+    #[derive(Clone)]
+    struct ProcSummary {
+        output:    Option<PrimitiveId>,
+        registers: HashMap<Symbol, PrimitiveId>,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl<'a> TypeChecker<'a> {
+        /// Creates a new checker aware of the registers already allocated in the context.
+        pub fn new(context: &'a SyntaxContext) -> Self {
+
+            let mut registers = HashMap::default();
+            let mut procs     = HashMap::default();
+
+            for (name, primitive) in context.registers() {
+                registers.insert(*name, primitive.id());
+
+                if let Primitive::Proc(proc) = primitive { procs.insert(*name, (proc.params.as_slice(), proc.body.as_ref())); }
+            } // for ..
+
+
+            TypeChecker {
+                registers,
+                procs,
+                hosts:     context.hosts(),
+                summaries: HashMap::default(),
+                active:    HashSet::default(),
+                untyped:   HashSet::default(),
+                errors:    Vec::default(),
+            } // TypeChecker ..
+        } // fn ..
+
+
+        /// Checks a chain of statements and returns every error found.
+        pub fn check(mut self, stms: &'a [Stm]) -> Result<(), Vec<InterpreterErr>> {
+            self.check_stms(stms);
+            if self.errors.is_empty() { Ok(()) } else { Err(self.errors) }
+        } // fn ..
+
+
+        /// Checks a chain of statements and returns the type of its first output.
+        fn check_stms(&mut self, stms: &'a [Stm]) -> Option<PrimitiveId> {
+            stms.iter().fold(None, |output, stm| {
+                let stm_output = self.check_stm(stm);
+                output.or(stm_output)
+            }) // fold()
+        } // fn ..
+
+
+        /// Checks a single statement and returns the type of its eventual output.
+        fn check_stm(&mut self, stm: &'a Stm) -> Option<PrimitiveId> {
+            match stm {
+                Stm::RegLet(index, name, decl) => {
+                    match self.check_decl(*index, decl) {
+                        Some(PrimitiveId::Void) => { self.registers.remove(name); },
+                        Some(id)                => { self.registers.insert(*name, id); },
+                        None                    => (),
+                    } None // match ..
+                }, // => ..
+                Stm::RegSet(index, name, decl) => {
+                    let found = self.check_decl(*index, decl);
+                    match (self.registers.get(name).cloned(), found) {
+                        (None, Some(found)) if self.untyped.contains(name) => { self.registers.insert(*name, found); },
+                        (None, _)                                          => self.errors.push(InterpreterErr::UndeclaredReg(*index, *name)),
+                        (Some(_), Some(PrimitiveId::Void))                 => { self.registers.remove(name); },
+                        (Some(expected), Some(found))                      => if expected != found { self.errors.push(InterpreterErr::MismatchedType(*index, expected, found)) },
+                        (Some(_), None)                                    => (),
+                    } None // match ..
+                }, // => ..
+                Stm::Macro(..) => None,
+                Stm::Define(_, name, params, decl) => {
+                    self.procs.insert(*name, (params.as_slice(), decl));
+                    self.summaries.remove(name);
+                    self.registers.insert(*name, PrimitiveId::Proc);
+                    None
+                }, // => ..
+                Stm::Table(index, name, table) => {
+                    self.expect_expr(*index, &table.dice, PrimitiveId::Int);
+                    for (_, _, decl) in &table.rows {
+                        let registers = self.registers.clone();
+                        self.check_decl(*index, decl);
+                        self.registers = registers;
+                    } // for ..
+
+                    self.registers.insert(*name, PrimitiveId::Table);
+                    None
+                }, // => ..
+                Stm::ItemSet(index, name, at, decl) => {
+                    self.expect_reg(*index, name, PrimitiveId::List);
+                    self.expect_expr(*index, at, PrimitiveId::Int);
+                    self.check_decl(*index, decl);
+                    None
+                }, // => ..
+                Stm::FieldSet(index, name, _, decl) => {
+                    self.expect_reg(*index, name, PrimitiveId::Record);
+                    self.check_decl(*index, decl);
+                    None
+                }, // => ..
+                Stm::Append(index, name, decl) => {
+                    self.expect_reg(*index, name, PrimitiveId::List);
+                    self.check_decl(*index, decl);
+                    None
+                }, // => ..
+                Stm::Remove(index, name, at) => {
+                    self.expect_reg(*index, name, PrimitiveId::List);
+                    self.expect_expr(*index, at, PrimitiveId::Int);
+                    None
+                }, // => ..
+                Stm::If(index, expr, decl) | Stm::While(index, expr, decl) => {
+                    self.expect_expr(*index, expr, PrimitiveId::Bool);
+
+                    let registers = self.registers.clone();
+                    let output    = self.check_decl(*index, decl);
+                    self.registers = registers;
+
+                    match stm { Stm::If(..) => output, _ => None }
+                }, // => ..
+                Stm::ForEach(index, id, name, expr, decl) => {
+                    self.expect_expr(*index, expr, PrimitiveId::List);
+
+                    let registers = self.registers.clone();
+                    match id {
+                        PrimitiveId::Void => { self.registers.remove(name); },
+                        _                 => { self.registers.insert(*name, id.clone()); },
+                    } // match ..
+
+                    self.check_decl(*index, decl);
+                    self.registers = registers;
+                    None
+                }, // => ..
+                Stm::For(index, name, bounds, decl) => {
+                    self.expect_expr(*index, &bounds.from, PrimitiveId::Int);
+                    self.expect_expr(*index, &bounds.to, PrimitiveId::Int);
+                    if let Some(step) = &bounds.step { self.expect_expr(*index, step, PrimitiveId::Int); }
+
+                    let registers = self.registers.clone();
+                    self.registers.insert(*name, PrimitiveId::Int);
+                    self.check_decl(*index, decl);
+                    self.registers = registers;
+                    None
+                }, // => ..
+                Stm::Tell(index, expr, _) => { self.infer_expr(*index, expr); None },
+                Stm::Assert(index, expr) => { self.expect_expr(*index, expr, PrimitiveId::Bool); None },
+                Stm::Mean(index, sample, target, tolerance) => {
+                    self.expect_expr(*index, &sample.runs, PrimitiveId::Int);
+                    self.expect_expr(*index, &sample.expr, PrimitiveId::Int);
+                    for bound in [target, tolerance] {
+                        if let Bound::Expr(expr) = bound { self.expect_expr(*index, expr, PrimitiveId::Int); }
+                    } // for ..
+                    None
+                }, // => ..
+                Stm::Never(index, sample) => {
+                    self.expect_expr(*index, &sample.runs, PrimitiveId::Int);
+                    self.expect_expr(*index, &sample.expr, PrimitiveId::Bool);
+                    None
+                }, // => ..
+                Stm::Out(index, expr)    => { Some(self.infer_expr(*index, expr)) },
+            } // match ..
+        } // fn ..
+
+
+        /// Checks a nested declaration and returns the type of its eventual output, forgetting the procedures it defines.
+        fn check_decl(&mut self, index: usize, decl: &'a Decl) -> Option<PrimitiveId> {
+
+            let saved = decl.defines().into_iter()
+                .map(|name| (name, self.registers.get(&name).cloned(), self.procs.get(&name).copied(), self.summaries.get(&name).cloned()))
+                .collect::<Vec<_>>();
+
+            let output = match decl {
+                Decl::Expr(expr)    => Some(self.infer_expr(index, expr)),
+                Decl::Closure(stms) => self.check_stms(stms),
+            }; // let ..
+
+            for (name, register, proc, summary) in saved.into_iter().rev() {
+                restore(&mut self.registers, name, register);
+                restore(&mut self.procs, name, proc);
+                restore(&mut self.summaries, name, summary);
+            } // for ..
+            output
+        } // fn ..
+
+
+        /// Infers the type of an expression, walking its operators iteratively.
+        fn infer_expr(&mut self, index: usize, expr: &'a Expr) -> PrimitiveId {
+
+            /// A step of the inference, the types of the operands being inferred before they are checked.
+            enum Task<'e> { Expr(&'e Expr), Val(&'e Val), Expect(PrimitiveId), Faces, Arith, Same, Pop, Push(PrimitiveId) }
+
+
+            let mut tasks = vec![Task::Expr(expr)];
+            let mut types = Vec::<PrimitiveId>::default();
+
+            macro_rules! pop { () => { types.pop().unwrap_or(PrimitiveId::Void) }; }
+
+            while let Some(task) = tasks.pop() {
+                match task {
+                    Task::Expr(Expr::Val(a))  => tasks.push(Task::Val(a)),
+                    Task::Val(Val::Expr(a))   => tasks.push(Task::Expr(a)),
+                    Task::Val(a)              => { let found = self.infer_val(index, a); types.push(found) },
+                    Task::Expr(Expr::Op(opr)) => match opr {
+                        Op::Neg(a)                                    => tasks.extend([Task::Push(PrimitiveId::Int), Task::Pop, Task::Arith, Task::Val(a)]),
+                        Op::Len(a)                                    => tasks.extend([Task::Push(PrimitiveId::Int), Task::Expect(PrimitiveId::List), Task::Val(a)]),
+                        Op::Add(a, b) | Op::Sub(a, b) | Op::Mul(a, b) => tasks.extend([Task::Push(PrimitiveId::Int), Task::Same, Task::Val(a), Task::Arith, Task::Expr(b)]),
+                        Op::Cmp(CmpTo::Eq(a) | CmpTo::Ne(a), b)       => tasks.extend([Task::Push(PrimitiveId::Bool), Task::Same, Task::Val(a), Task::Expr(b)]),
+                        Op::Cmp(cmp, b)                               => tasks.extend([Task::Push(PrimitiveId::Bool), Task::Expect(PrimitiveId::Int), Task::Val(cmp.val()), Task::Expect(PrimitiveId::Int), Task::Expr(b)]),
+                        Op::Div(a, b)                                 => tasks.extend([Task::Push(PrimitiveId::Int), Task::Expect(PrimitiveId::Int), Task::Val(a), Task::Expect(PrimitiveId::Int), Task::Expr(b)]),
+                        Op::Dice(a, b, c)                             => {
+                            tasks.push(Task::Push(PrimitiveId::Int));
+                            if let Some(c) = c { tasks.extend([Task::Expect(PrimitiveId::Int), Task::Val(c.val())]) }
+                            tasks.extend([Task::Faces, Task::Val(a), Task::Expect(PrimitiveId::Int), Task::Expr(b)]);
+                        }, // => ..
+                        Op::Pool(a, b, c)                             => {
+                            tasks.push(Task::Push(PrimitiveId::Int));
+                            if let Some(c) = c.keep() { tasks.extend([Task::Expect(PrimitiveId::Int), Task::Val(c)]) }
+                            tasks.extend([Task::Faces, Task::Val(a), Task::Expect(PrimitiveId::Int), Task::Expr(b)]);
+                        }, // => ..
+                    }, // => ..
+                    Task::Expect(expected) => {
+                        let found = pop!();
+                        if found != expected { self.errors.push(InterpreterErr::MismatchedType(index, expected, found)) }
+                    }, // => ..
+                    Task::Faces => match pop!() {
+                        PrimitiveId::Int | PrimitiveId::List => (),
+                        found                                => self.errors.push(InterpreterErr::MismatchedType(index, PrimitiveId::Int, found)),
+                    }, // => ..
+                    Task::Arith => match pop!() {
+                        found @ (PrimitiveId::Int | PrimitiveId::Bool) => types.push(found),
+                        found                                          => { self.errors.push(InterpreterErr::MismatchedType(index, PrimitiveId::Int, found)); types.push(PrimitiveId::Int) },
+                    }, // => ..
+                    Task::Same => {
+                        let (found, expected) = (pop!(), pop!());
+                        if found != expected { self.errors.push(InterpreterErr::MismatchedType(index, expected, found)) }
+                    }, // => ..
+                    Task::Pop        => { types.pop(); },
+                    Task::Push(id)   => types.push(id),
+                } // match ..
+            } // while ..
+
+            pop!()
+        } // fn ..
+
+
+        /// Infers the type of a value.
+        fn infer_val(&mut self, index: usize, val: &'a Val) -> PrimitiveId {
+            match val {
+                Val::Int(..)            => PrimitiveId::Int,
+                Val::Bool(..)           => PrimitiveId::Bool,
+                Val::Text(..)           => PrimitiveId::Text,
+                Val::Table(..)          => PrimitiveId::Table,
+                Val::Void               => PrimitiveId::Void,
+                Val::Expr(expr)         => self.infer_expr(index, expr),
+                Val::Lambda(params, decl) => {
+                    let (registers, untyped) = (self.registers.clone(), self.untyped.clone());
+                    for param in params {
+                        self.registers.remove(param);
+                        self.untyped.insert(*param);
+                    } // for ..
+
+                    self.check_decl(index, decl);
+                    self.registers = registers;
+                    self.untyped   = untyped;
+                    PrimitiveId::Proc
+                }, // => ..
+                Val::Ask(id, ..)        => id.clone(),
+                Val::Proc(..)           => PrimitiveId::Proc,
+                Val::List(items)        => { items.iter().for_each(|item| { self.infer_expr(index, item); }); PrimitiveId::List },
+                Val::Record(items)      => { items.iter().for_each(|(_, item)| { self.infer_expr(index, item); }); PrimitiveId::Record },
+                Val::Field(id, name, _) => { self.expect_reg(index, name, PrimitiveId::Record); id.clone() },
+                Val::Ref(id, name) => {
+                    match (id, self.registers.get(name).cloned()) {
+                        (_, None) if self.untyped.contains(name)                   => { self.registers.insert(*name, id.clone()); },
+                        (_, None)                                                  => self.errors.push(InterpreterErr::UndeclaredReg(index, *name)),
+                        (PrimitiveId::Proc, Some(PrimitiveId::Proc))               => (),
+                        (_, Some(PrimitiveId::Proc))                               => self.check_call(index, id, name, Vec::default()),
+                        (id, Some(PrimitiveId::Table)) if *id != PrimitiveId::Proc => (),
+                        (_, Some(found))                                           => if *id != found { self.errors.push(InterpreterErr::MismatchedType(index, id.clone(), found)) },
+                    } id.clone() // match ..
+                }, // => ..
+                Val::Call(id, name, args) => {
+                    let args = args.iter().map(|arg| self.infer_expr(index, arg)).collect();
+                    match self.registers.get(name).cloned() {
+                        None if self.untyped.contains(name)                => { self.registers.insert(*name, PrimitiveId::Proc); },
+                        None if self.hosts.contains_key(name)              => self.check_host(index, id, name, args),
+                        None                                               => self.errors.push(InterpreterErr::UndeclaredReg(index, *name)),
+                        Some(PrimitiveId::Proc)                            => self.check_call(index, id, name, args),
+                        Some(PrimitiveId::List) | Some(PrimitiveId::Table) => match args.as_slice() {
+                            [PrimitiveId::Int] => (),
+                            [found]            => self.errors.push(InterpreterErr::MismatchedType(index, PrimitiveId::Int, found.clone())),
+                            _                  => self.errors.push(InterpreterErr::WrongArity(1usize, args.len())),
+                        }, // => ..
+                        Some(found)                                        => self.errors.push(InterpreterErr::MismatchedType(index, PrimitiveId::Proc, found)),
+                    } id.clone() // match ..
+                }, // => ..
+            } // match ..
+        } // fn ..
+
+
+        /// Reports an error if a procedure called with the given arguments does not output the expected type.
+        fn check_call(&mut self, index: usize, id: &PrimitiveId, name: &Symbol, args: Vec<PrimitiveId>) {
+            if let Some(found) = self.check_proc(index, name, args) {
+                if *id != found { self.errors.push(InterpreterErr::MismatchedType(index, id.clone(), found)) }
+            } // if ..
+        } // fn ..
+
+
+        /// Reports an error if a host function is called with arguments or for an output of the wrong types.
+        fn check_host(&mut self, index: usize, id: &PrimitiveId, name: &Symbol, args: Vec<PrimitiveId>) {
+
+            let host = &self.hosts[name];
+            if host.params.len() != args.len() { return self.errors.push(InterpreterErr::WrongArity(host.params.len(), args.len())) }
+
+            for (param, arg) in host.params.iter().zip(args) {
+                if *param != arg { self.errors.push(InterpreterErr::MismatchedType(index, param.clone(), arg)) }
+            } // for ..
+            if *id != host.output { self.errors.push(InterpreterErr::MismatchedType(index, id.clone(), host.output.clone())) }
+        } // fn ..
+
+
+        /// Checks a procedure body once and returns the type of its eventual output.
+        fn check_proc(&mut self, index: usize, name: &Symbol, args: Vec<PrimitiveId>) -> Option<PrimitiveId> {
+
+            let (params, decl) = *self.procs.get(name)?;
+            if params.len() != args.len() {
+                self.errors.push(InterpreterErr::WrongArity(params.len(), args.len()));
+                return None;
+            } // if ..
+
+            if let Some(summary) = self.summaries.get(name) {
+                self.registers.extend(summary.registers.clone());
+                return summary.output.clone();
+            } // if ..
+
+            if !self.active.insert(*name) { return None; }
+
+
+            let before = self.registers.clone();
+            for (param, arg) in params.iter().zip(args) {
+                match arg {
+                    PrimitiveId::Void => { self.registers.remove(param); },
+                    _                 => { self.registers.insert(*param, arg); },
+                } // match ..
+            } // for ..
+
+            let output    = self.check_decl(index, decl);
+            let registers = self.registers.iter()
+                .filter(|(name, id)| !params.contains(*name) && before.get(*name) != Some(*id))
+                .map(|(name, id)| (*name, id.clone()))
+                .collect();
+
+            for param in params {
+                match before.get(param) {
+                    Some(id) => { self.registers.insert(*param, id.clone()); },
+                    None     => { self.registers.remove(param); },
+                } // match ..
+            } // for ..
+
+            self.active.remove(name);
+            self.summaries.insert(*name, ProcSummary { output: output.clone(), registers });
+
+            output
+        } // fn ..
+
+
+        /// Reports an error if a register does not hold the expected type.
+        fn expect_reg(&mut self, index: usize, name: &Symbol, expected: PrimitiveId) {
+            match self.registers.get(name).cloned() {
+                None if self.untyped.contains(name) => { self.registers.insert(*name, expected); },
+                None                                => self.errors.push(InterpreterErr::UndeclaredReg(index, *name)),
+                Some(found) if found == expected    => (),
+                Some(found)                         => self.errors.push(InterpreterErr::MismatchedType(index, expected, found)),
+            } // match ..
+        } // fn ..
+
+
+        /// Reports an error if an expression does not have the expected type.
+        fn expect_expr(&mut self, index: usize, expr: &'a Expr, expected: PrimitiveId) {
+            let found = self.infer_expr(index, expr);
+            if found != expected { self.errors.push(InterpreterErr::MismatchedType(index, expected, found)) }
+        } // fn ..
+    } // impl ..
+
+
+    /// Puts back the entry a map held for a given name, or removes it if it held none.
+    fn restore<V>(map: &mut HashMap<Symbol, V>, name: Symbol, value: Option<V>) {
+        match value {
+            Some(value) => { map.insert(name, value); },
+            None        => { map.remove(&name); },
+        } // match ..
+    } // fn ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{SyntaxContext, SyntaxElement, TypeChecker, Stm, WsToken, InterpreterErr, PrimitiveId};
+
+        /// Scans and checks a script in a fresh context.
+        fn check(src: &str) -> Result<(), Vec<InterpreterErr>> {
+            let mut context = SyntaxContext::default();
+            context.push(src).unwrap();
+            let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+            TypeChecker::new(&context).check(&stms)
+        } // fn ..
+
+        #[test]
+        fn arithmetic_on_booleans_is_an_integer() {
+            assert!(check("let [b] <- 1 = 1; let [x] <- boolean [b] + boolean [b]; let [y] <- integer [x] * 2;").is_ok());
+            assert!(check("let [b] <- 1 = 1; let [x] <- - boolean [b]; let [y] <- integer [x];").is_ok());
+
+            match check("let [b] <- 1 = 1; let [x] <- boolean [b] * boolean [b]; let [y] <- boolean [x];") {
+                Err(errs) => assert!(matches!(errs.as_slice(), [InterpreterErr::MismatchedType(_, PrimitiveId::Bool, PrimitiveId::Int)])),
+                Ok(())    => panic!("a boolean product type checked as a boolean"),
+            } // match ..
+        } // fn ..
+
+        #[test]
+        fn lambda_bodies_are_checked() {
+            assert!(check("let [f] <- proc([a]): out -> integer [a] + 1; >>>>;").is_ok());
+            assert!(check("let [n] <- 2; let [f] <- proc([a], [g]): set [n] <- integer [g](integer [a]); out -> integer [n]; >>>>;").is_ok());
+
+            match check("let [f] <- proc([a]): let [x] <- integer [a]; let [y] <- boolean [x]; >>>>;") {
+                Err(errs) => assert!(matches!(errs.as_slice(), [InterpreterErr::MismatchedType(_, PrimitiveId::Bool, PrimitiveId::Int)])),
+                Ok(())    => panic!("a lambda misusing its parameter type checked"),
+            } // match ..
+            assert!(matches!(check("let [f] <- proc([a]): out -> integer [b]; >>>>;"), Err(errs) if matches!(errs.as_slice(), [InterpreterErr::UndeclaredReg(..)])));
+            assert!(matches!(check("let [f] <- proc([a]): out -> integer [a]; >>>>; let [x] <- integer [a];"), Err(errs) if matches!(errs.as_slice(), [InterpreterErr::UndeclaredReg(..)])));
+        } // fn ..
+
+        #[test]
+        fn nested_definitions_are_scoped() {
+            assert!(check("define [f]: out -> 1; >>>> if | 1 = 1 |: define [f]([a]): out -> 'x'; >>>> tell -> text [f](1); >>>> let [y] <- integer [f];").is_ok());
+            assert!(matches!(check("define [g]: define [h]: out -> 1; >>>> out -> integer [h]; >>>> let [x] <- integer [g]; let [y] <- integer [h];"), Err(errs) if matches!(errs.as_slice(), [InterpreterErr::UndeclaredReg(..)])));
+        } // fn ..
+    } // mod ..
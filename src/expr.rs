@@ -2,13 +2,17 @@
 // D E P E N D E N C I E S
 //#########################
 
-    use rusty_toolkit::{FixedStr, L16};
+    use std::fmt;
+
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
 
     use crate::{
         SyntaxContext, SyntaxElement,
         InterpreterErr,
         WsToken,
-        PrimitiveId, Primitive,
+        Stm, Decl, Bound,
+        PrimitiveId, Primitive, Proc, Table, Record,
+        Symbol,
     }; // use ..
 
 
@@ -16,10 +20,29 @@
 // D E F I N I T I O N S
 //#######################
 
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)] pub enum Expr  { Val(Val), Op(Op), }
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)] pub enum Op    { Neg(Val), Add(Val, Box<Expr>), Sub(Val, Box<Expr>), Mul(Val, Box<Expr>), Div(Val, Box<Expr>), Dice(Val, Box<Expr>, Option<CmpTo>), Cmp(CmpTo, Box<Expr>)}
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)] pub enum Val   { Int(i16), Bool(bool), Ref(PrimitiveId, FixedStr<L16>), Expr(Box<Expr>), Void, }
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)] pub enum CmpTo { Eq(Val), Ne(Val), Gt(Val), Ge(Val), Lt(Val), Le(Val) }
+    #[derive(Debug, Hash, PartialEq, Eq, Clone)]                         pub enum Expr  { Val(Val), Op(Op), }
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)] pub enum Op    { Neg(Val), Len(Val), Add(Val, Box<Expr>), Sub(Val, Box<Expr>), Mul(Val, Box<Expr>), Div(Val, Box<Expr>), Dice(Val, Box<Expr>, Option<CmpTo>), Pool(Val, Box<Expr>, Box<Pool>), Cmp(CmpTo, Box<Expr>)}
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)] pub enum Val   { Int(i16), Bool(bool), Text(String), Ref(PrimitiveId, Symbol), Field(PrimitiveId, Symbol, Vec<Symbol>), Call(PrimitiveId, Symbol, Vec<Expr>), Lambda(Vec<Symbol>, Box<Decl>), Proc(Box<Proc>), List(Vec<Expr>), Table(Box<Table>), Record(Vec<(Symbol, Expr)>), Ask(PrimitiveId, String, Option<(i16, i16)>), Expr(Box<Expr>), Void, }
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)] pub enum CmpTo { Eq(Val), Ne(Val), Gt(Val), Ge(Val), Lt(Val), Le(Val) }
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)] pub enum Keep  { High(Val), Low(Val) }
+
+    /// An expression with parameters, expanded wherever it is used as the scanning goes.
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)] pub struct Macro { pub(crate) params: Vec<Symbol>, pub(crate) body: Expr }
+
+    /// The faces a die lands on: from one to a given face, or the items of a list for custom dice.
+    #[derive(Debug, Clone, PartialEq, Eq)] pub(crate) enum Faces { Range(i16), List(Vec<i16>) }
+
+    /// The modifiers of a dice pool: dice exploding on their highest face, and only some of the highest or lowest dice being kept.
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)] pub struct Pool { pub(crate) explode: bool, pub(crate) keep: Option<Keep> }
+
+    /// How an expression is written and read, see the serde implementations of `Expr`.
+    #[derive(Serialize, Deserialize)] #[serde(remote = "Expr")] enum ExprDef { Val(Val), Op(Op), }
+
+    /// How close to the end of the stack writing or reading an expression may go before moving to a new stack.
+    const RED_ZONE:   usize = 64usize * 1024usize;
+
+    /// How large the stacks written or read expressions move to are.
+    const STACK_SIZE: usize = 2usize * 1024usize * 1024usize;
 
 
 //###############################
@@ -35,117 +58,210 @@
             input:     Self::Input,
             end_token: WsToken,
         ) -> Result<Self::Output, InterpreterErr> {
+            let expr = Self::scan_until(context, input, &[end_token]);
+            context.next();
+            expr
+        } // fn ..
+    } // impl ..
+
+
+    impl Expr {
+        /// Scans the current syntax context to get an expression ending right before any of the given tokens.
+        pub(crate) fn scan_until(
+            context:    &mut SyntaxContext,
+            input:      Option<Self>,
+            end_tokens: &[WsToken],
+        ) -> Result<Self, InterpreterErr> { context.nested(|context| Self::scan_nested(context, input, end_tokens)) }
+
+
+        /// Scans an expression ending right before any of the given tokens, once its nesting is accounted for.
+        fn scan_nested(
+            context:    &mut SyntaxContext,
+            input:      Option<Self>,
+            end_tokens: &[WsToken],
+        ) -> Result<Self, InterpreterErr> {
 
             fn op<F: Fn(Expr, Val) -> Expr>(
                 context:   &mut SyntaxContext,
-                end_token: WsToken,
                 a:         Expr,
                 func:      F,
-            ) -> Result<Expr, InterpreterErr> { context.next(); let b = next_value(context).unwrap(); Expr::scan(context, Some(func(a, b)), end_token) }
+            ) -> Result<Expr, InterpreterErr> { context.next(); let b = next_value(context)?; Ok(func(a, b)) }
+
+
+            /// An operator waiting for the value on its right to be scanned.
+            enum Pending { Neg, Len, Mul(Val), Div(Val), Dice(Val), Cmp(Val, Val, WsToken) }
 
 
             fn next_value(context: &mut SyntaxContext) -> Result<Val, InterpreterErr> {
-                let a = match *context.peek().unwrap() {
 
-                    WsToken::DigitLit(a) => { context.next(); Ok(Val::Int(a)) },
-                    WsToken::Ellipsis    => { context.next(); Ok(Val::Void) },
+                let mut pending = Vec::<Pending>::default();
+                loop {
+                    let a = match *context.peek().unwrap() {
 
-                    WsToken::Minus       => { context.next(); let b = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Neg(b))))) }
+                        WsToken::DigitLit(a) => { context.next(); Val::Int(a) },
+                        WsToken::StrLit(ref a) => { let a = a.clone(); context.next(); Val::Text(a) },
+                        WsToken::Ellipsis    => { context.next(); Val::Void },
+                        WsToken::Ask         => context.next_ask()?,
 
-                    WsToken::LeftParen   => { context.next(); Ok(Val::Expr(Box::new(Expr::scan(context, None, WsToken::RightParen)?))) },
-                    _                    => { let id = PrimitiveId::try_from(context.peek().unwrap())?; context.next(); let name = context.next_ref()?; Ok(Val::Ref(id, name)) },
+                        WsToken::Minus       => { context.next(); pending.push(Pending::Neg); continue; },
+                        WsToken::Length      => { context.next(); pending.push(Pending::Len); continue; },
 
-                }?; // let ..
+                        WsToken::LeftParen   => { context.next(); Val::Expr(Box::new(Expr::scan(context, None, WsToken::RightParen)?)) },
+                        WsToken::Proc if context.peek_nth(1usize) != Some(&WsToken::LeftBracket) => {
+                            context.next();
+                            let params = context.next_params()?;
+                            match context.peek() {
+                                Some(WsToken::Colon) => { context.next(); Val::Lambda(params, Box::new(Decl::Closure(Stm::scan(context, Vec::default(), WsToken::Eos)?))) },
+                                _                    => return Err(InterpreterErr::ExpectedADecl(context.token_index())),
+                            } // match ..
+                        }, // => ..
+                        WsToken::List if context.peek_nth(1usize) == Some(&WsToken::LeftParen) => {
+                            context.next();
+                            Val::List(context.next_args()?.unwrap_or_default())
+                        }, // => ..
+                        WsToken::Record if context.peek_nth(1usize) == Some(&WsToken::LeftParen) => {
+                            context.next();
+                            Val::Record(context.next_fields()?)
+                        }, // => ..
+                        _                    => {
+                            let id           = PrimitiveId::try_from(context.peek().unwrap())?;
+                            context.next();
+                            let (name, path) = context.next_path()?;
+                            match context.next_args()? {
+                                _ if !path.is_empty() => Val::Field(id, name, path),
+                                args if context.find_macro(&name).is_some() => {
+                                    let body = context.find_macro(&name).unwrap().expand(context.token_index(), args.unwrap_or_default())?;
+                                    Val::Expr(Box::new(body))
+                                }, // => ..
+                                Some(args)            => Val::Call(id, name, args),
+                                None                  => Val::Ref(id, name),
+                            } // match ..
+                        }, // => ..
 
+                    }; // let ..
 
-                match *context.peek().unwrap() {
-                    WsToken::Star  => { context.next(); let b = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Mul(b, Box::new(Expr::Val(a)))))))  }
-                    WsToken::Slash => { context.next(); let b = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Div(b, Box::new(Expr::Val(a))))))) }
-                    WsToken::Dice  => { context.next(); let b = next_value(context)?; match *context.peek().unwrap() {
-                        WsToken::Colon => { context.next(); let token = context.peek().unwrap(); match token {
-                            WsToken::Eq => { context.next(); let c = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), Some(CmpTo::Eq(c))))))) },
-                            WsToken::Ne => { context.next(); let c = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), Some(CmpTo::Ne(c))))))) },
-                            WsToken::Gt => { context.next(); let c = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), Some(CmpTo::Gt(c))))))) },
-                            WsToken::Ge => { context.next(); let c = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), Some(CmpTo::Ge(c))))))) },
-                            WsToken::Lt => { context.next(); let c = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), Some(CmpTo::Lt(c))))))) },
-                            WsToken::Le => { context.next(); let c = next_value(context)?; Ok(Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), Some(CmpTo::Le(c))))))) },
-                            _           => Err(InterpreterErr::WrongToken(context.token_index(), token.clone())),
-                            }}, // => ..
-                        _ => Ok(Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), None))))),
-                    }}, // => ..
-                    _  => Ok(a),
-                } // match ..
+
+                    match *context.peek().unwrap() {
+                        WsToken::Star  => { context.next(); pending.push(Pending::Mul(a));  continue; },
+                        WsToken::Slash => { context.next(); pending.push(Pending::Div(a));  continue; },
+                        WsToken::Dice  => { context.next(); pending.push(Pending::Dice(a)); continue; },
+                        _              => (),
+                    } // match ..
+
+
+                    let mut b = a;
+                    loop {
+                        b = match pending.pop() {
+                            Some(Pending::Neg)        => Val::Expr(Box::new(Expr::Op(Op::Neg(b)))),
+                            Some(Pending::Len)        => Val::Expr(Box::new(Expr::Op(Op::Len(b)))),
+                            Some(Pending::Mul(a))     => Val::Expr(Box::new(Expr::Op(Op::Mul(b, Box::new(Expr::Val(a)))))),
+                            Some(Pending::Div(a))     => Val::Expr(Box::new(Expr::Op(Op::Div(b, Box::new(Expr::Val(a)))))),
+                            Some(Pending::Dice(a))    => match *context.peek().unwrap() {
+                                WsToken::Colon => { context.next(); let token = context.peek().unwrap(); match token {
+                                    WsToken::Eq | WsToken::Ne | WsToken::Gt | WsToken::Ge | WsToken::Lt | WsToken::Le => {
+                                        let token = token.clone();
+                                        context.next();
+                                        pending.push(Pending::Cmp(a, b, token));
+                                        break;
+                                    }, // => ..
+                                    _ => return Err(InterpreterErr::WrongToken(context.token_index(), token.clone())),
+                                }}, // => ..
+                                _ => Val::Expr(Box::new(Expr::Op(Op::Dice(b, Box::new(Expr::Val(a)), None)))),
+                            }, // => ..
+                            Some(Pending::Cmp(a, f, token)) => Val::Expr(Box::new(Expr::Op(Op::Dice(f, Box::new(Expr::Val(a)), Some(match token {
+                                WsToken::Eq => CmpTo::Eq(b),
+                                WsToken::Ne => CmpTo::Ne(b),
+                                WsToken::Gt => CmpTo::Gt(b),
+                                WsToken::Ge => CmpTo::Ge(b),
+                                WsToken::Lt => CmpTo::Lt(b),
+                                _           => CmpTo::Le(b),
+                            }))))), // => ..
+                            None                      => return Ok(b),
+                        }; // b ..
+                    } // loop ..
+                } // loop ..
             } // fn ..
 
 
-            if *context.peek().unwrap() == end_token {
-                context.next();
-                match input {
-                    Some(input) => Ok(input),
-                    None        => Err(InterpreterErr::ExpectedAnExpr(context.token_index())),
-                } // match ..
-            } else {
-                match input {
-                    
+            let mut input = input;
+            loop {
+                if end_tokens.contains(context.peek().unwrap()) {
+                    break match input {
+                        Some(input) => Ok(input),
+                        None        => Err(InterpreterErr::ExpectedAnExpr(context.token_index())),
+                    } // match ..
+                } // if ..
+
+                input = Some(match input {
+
                     Some(expr) => { let token = context.peek().unwrap(); match token {
-                        WsToken::Plus  => op(context, end_token, expr, |a, b| Expr::Op(Op::Add(b, Box::new(a)))),
-                        WsToken::Minus => op(context, end_token, expr, |a, b| Expr::Op(Op::Sub(b, Box::new(a)))),
-                        WsToken::Star  => op(context, end_token, expr, |a, b| Expr::Op(Op::Mul(b, Box::new(a)))),
-                        WsToken::Slash => op(context, end_token, expr, |a, b| Expr::Op(Op::Div(b, Box::new(a)))),
-                        WsToken::Dice  => op(context, end_token, expr, |a, b| Expr::Op(Op::Dice(b, Box::new(a), None))),
-                        WsToken::Eq    => op(context, end_token, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Eq(b), Box::new(a)))),
-                        WsToken::Ne    => op(context, end_token, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Ne(b), Box::new(a)))),
-                        WsToken::Gt    => op(context, end_token, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Gt(b), Box::new(a)))),
-                        WsToken::Ge    => op(context, end_token, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Ge(b), Box::new(a)))),
-                        WsToken::Lt    => op(context, end_token, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Lt(b), Box::new(a)))),
-                        WsToken::Le    => op(context, end_token, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Le(b), Box::new(a)))),
-                        _              => Err(InterpreterErr::WrongToken(context.token_index(), token.clone())),
+                        WsToken::Plus  => op(context, expr, |a, b| Expr::Op(Op::Add(b, Box::new(a))))?,
+                        WsToken::Minus => op(context, expr, |a, b| Expr::Op(Op::Sub(b, Box::new(a))))?,
+                        WsToken::Star  => op(context, expr, |a, b| Expr::Op(Op::Mul(b, Box::new(a))))?,
+                        WsToken::Slash => op(context, expr, |a, b| Expr::Op(Op::Div(b, Box::new(a))))?,
+                        WsToken::Dice  => op(context, expr, |a, b| Expr::Op(Op::Dice(b, Box::new(a), None)))?,
+                        WsToken::Eq    => op(context, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Eq(b), Box::new(a))))?,
+                        WsToken::Ne    => op(context, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Ne(b), Box::new(a))))?,
+                        WsToken::Gt    => op(context, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Gt(b), Box::new(a))))?,
+                        WsToken::Ge    => op(context, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Ge(b), Box::new(a))))?,
+                        WsToken::Lt    => op(context, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Lt(b), Box::new(a))))?,
+                        WsToken::Le    => op(context, expr, |a, b| Expr::Op(Op::Cmp(CmpTo::Le(b), Box::new(a))))?,
+                        _              => break Err(InterpreterErr::WrongToken(context.token_index(), token.clone())),
                     }}, // => ..
-                    None => match *context.peek().unwrap() {
-                        _ => { let val = next_value(context)?; Self::scan(context, Some(Expr::Val(val)), end_token) },
-                    }, // => ..
-            }} // if ..
+                    None => Expr::Val(next_value(context)?),
+                }); // input ..
+            } // loop ..
         } // fn ..
-    } // impl ..
 
 
-    impl Expr {
         pub(crate) fn as_val(
             &self,
             context: &mut SyntaxContext,
         ) -> Result<Val, InterpreterErr> {
 
             match self {
-                Expr::Val(value) => match value {
-                    Val::Expr(expr)    => expr.as_val(context),
-                    Val::Ref(id, name) => match (id, context.reg(name)?.clone()) {
-                        (PrimitiveId::Int,  primitive) => primitive.as_val(context)?.as_int(context),
-                        (PrimitiveId::Bool, primitive) => primitive.as_val(context)?.as_bool(context),
-                        (PrimitiveId::Void, primitive) => primitive.as_val(context)?.as_void(context),
-                        (PrimitiveId::Proc, primitive) => primitive.as_val(context)?.as_proc(context),
+                Expr::Val(value)      => match value {
+                    Val::Expr(expr)            => expr.as_val(context),
+                    Val::Ref(id, name)         => { let primitive = context.reg(name)?.clone(); Val::typed(context, id, primitive) },
+                    Val::Field(id, name, path) => { let primitive = context.reg(name)?.field(path)?.clone(); Val::typed(context, id, primitive) },
+                    Val::Call(id, name, args) => {
+                        let args = args.iter()
+                            .map(|arg| arg.as_val(context).and_then(|val| val.as_primitive(context)))
+                            .collect::<Result<Vec<Primitive>, InterpreterErr>>()?;
+                        if let Some(host) = context.find_host(name) { return Val::typed(context, id, host.call(args)?) }
+                        match context.reg(name)?.clone() {
+                            Primitive::Proc(proc)   => proc.call(context, args)?.cast(context, id),
+                            Primitive::List(items)  => {
+                                if args.len() != 1usize { return Err(InterpreterErr::WrongArity(1usize, args.len())) }
+                                let index = Expr::Val(Val::from(args[0usize].clone())).as_int(context)?;
+                                Val::typed(context, id, Primitive::item(&items, index)?.clone())
+                            }, // => ..
+                            Primitive::Table(table) => {
+                                if args.len() != 1usize { return Err(InterpreterErr::WrongArity(1usize, args.len())) }
+                                let roll = Expr::Val(Val::from(args[0usize].clone())).as_int(context)?;
+                                table.lookup(context, roll)?.cast(context, id)
+                            }, // => ..
+                            _                       => Err(InterpreterErr::NotAProc),
+                        } // match ..
                     }, // => ..
+                    Val::Lambda(params, decl) => Ok(Val::Proc(Box::new(Proc::capture(context, params, decl)))),
+                    Val::Ask(id, prompt, range) => Ok(Val::from(context.ask(id, prompt, *range)?)),
+                    Val::List(items)          => Ok(Val::List(items.iter()
+                        .map(|item| item.as_val(context).and_then(|val| val.as_primitive(context)).map(|item| Expr::Val(Val::from(item))))
+                        .collect::<Result<Vec<Expr>, InterpreterErr>>()?)),
+                    Val::Record(fields)       => Ok(Val::Record(fields.iter()
+                        .map(|(name, field)| field.as_val(context).and_then(|val| val.as_primitive(context)).map(|field| (*name, Expr::Val(Val::from(field)))))
+                        .collect::<Result<Vec<(Symbol, Expr)>, InterpreterErr>>()?)),
                     _ => Ok(value.clone()),
                 }, // match ..
-                Expr::Op(opr) => Ok(match opr {
-                    Op::Neg(a)               => Val::Int(-Expr::Val(a.clone()).as_int(context)?),
-                    Op::Add(a, b)            => Val::Int( b.as_int(context)? + Expr::Val(a.clone()).as_int(context)?),
-                    Op::Sub(a, b)            => Val::Int( b.as_int(context)? - Expr::Val(a.clone()).as_int(context)?),
-                    Op::Mul(a, b)            => Val::Int( b.as_int(context)? * Expr::Val(a.clone()).as_int(context)?),
-                    Op::Div(a, b)            => Val::Int( b.as_int(context)? / Expr::Val(a.clone()).as_int(context)?),
-                    Op::Dice(a, b, c)        => Val::Int( b.dice_throw(context, Expr::Val(a.clone()), c.clone())?),
-                    Op::Cmp(CmpTo::Eq(a), b) => Val::Bool(b.as_int(context)? == Expr::Val(a.clone()).as_int(context)?),
-                    Op::Cmp(CmpTo::Ne(a), b) => Val::Bool(b.as_int(context)? != Expr::Val(a.clone()).as_int(context)?),
-                    Op::Cmp(CmpTo::Gt(a), b) => Val::Bool(b.as_int(context)? >= Expr::Val(a.clone()).as_int(context)?),
-                    Op::Cmp(CmpTo::Ge(a), b) => Val::Bool(b.as_int(context)? >  Expr::Val(a.clone()).as_int(context)?),
-                    Op::Cmp(CmpTo::Lt(a), b) => Val::Bool(b.as_int(context)? <  Expr::Val(a.clone()).as_int(context)?),
-                    Op::Cmp(CmpTo::Le(a), b) => Val::Bool(b.as_int(context)? <= Expr::Val(a.clone()).as_int(context)?),
-                }), // => ..
+                Expr::Op(Op::Cmp(..)) => Ok(Val::Bool(self.as_int(context)? != 0i16)),
+                Expr::Op(..)          => Ok(Val::Int(self.as_int(context)?)),
             } // match ..
         } // fn ..
 
 
         /// Gets an integer output from an expression.
-        fn as_int(
+        pub(crate) fn as_int(
             &self,
             context: &mut SyntaxContext,
         ) -> Result<i16, InterpreterErr> {
@@ -156,28 +272,82 @@
                     Val::Bool(bool)   => Ok(if bool { 1i16 } else { 0i16 }),
                     Val::Expr(expr)   => expr.as_int(context),
                     Val::Ref(_, name) => { let value = context.reg(&name)?.clone().as_val(context)?; value_to_i16(context, value) },
+                    Val::Proc(proc)   => { let value = proc.call(context, Vec::default())?; value_to_i16(context, value) },
+                    Val::Table(table) => { let value = table.roll(context)?; value_to_i16(context, value) },
+                    Val::Text(..)     => Ok(0i16),
+                    Val::Field(..)    |
+                    Val::Call(..)     |
+                    Val::Ask(..)      |
+                    Val::Lambda(..)   => { let value = Expr::Val(value).as_val(context)?; value_to_i16(context, value) },
+                    Val::List(..)     => Ok(Expr::Val(value).items(context)?.len() as i16),
+                    Val::Record(..)   => Ok(Expr::Val(value).fields(context)?.len() as i16),
                     Val::Void         => Ok(0i16),
                 } // match ..
             } // fn ..
 
 
-            match self {
-                Expr::Val(value) => value_to_i16(context, value.clone()),
-                Expr::Op(opr) => Ok(match opr {
-                    Op::Neg(a)               => -Expr::Val(a.clone()).as_int(context)?,
-                    Op::Add(a, b)            =>  b.as_int(context)? + Expr::Val(a.clone()).as_int(context)?,
-                    Op::Sub(a, b)            =>  b.as_int(context)? - Expr::Val(a.clone()).as_int(context)?,
-                    Op::Mul(a, b)            =>  b.as_int(context)? * Expr::Val(a.clone()).as_int(context)?,
-                    Op::Div(a, b)            =>  b.as_int(context)? / Expr::Val(a.clone()).as_int(context)?,
-                    Op::Dice(a, b, c)        =>  b.dice_throw(context, Expr::Val(a.clone()), c.clone())?,
-                    Op::Cmp(CmpTo::Eq(a), b) => if b.as_int(context)? == Expr::Val(a.clone()).as_int(context)? { 1i16 } else { 0i16 },
-                    Op::Cmp(CmpTo::Ne(a), b) => if b.as_int(context)? != Expr::Val(a.clone()).as_int(context)? { 1i16 } else { 0i16 },
-                    Op::Cmp(CmpTo::Gt(a), b) => if b.as_int(context)? >= Expr::Val(a.clone()).as_int(context)? { 1i16 } else { 0i16 },
-                    Op::Cmp(CmpTo::Ge(a), b) => if b.as_int(context)? >  Expr::Val(a.clone()).as_int(context)? { 1i16 } else { 0i16 },
-                    Op::Cmp(CmpTo::Lt(a), b) => if b.as_int(context)? <  Expr::Val(a.clone()).as_int(context)? { 1i16 } else { 0i16 },
-                    Op::Cmp(CmpTo::Le(a), b) => if b.as_int(context)? <= Expr::Val(a.clone()).as_int(context)? { 1i16 } else { 0i16 },
-                }), // => ..
-            } // match ..
+            /// A step of the evaluation, operands being evaluated before the operator applying to them.
+            enum Task<'e> { Expr(&'e Expr), Val(&'e Val), Faces(&'e Val), Range, Apply(&'e Op) }
+
+
+            let mut tasks = vec![Task::Expr(self)];
+            let mut ints  = Vec::<i16>::default();
+            let mut faces = Vec::<Faces>::default();
+
+            macro_rules! pop { () => { ints.pop().unwrap_or_default() }; }
+
+            while let Some(task) = tasks.pop() {
+                match task {
+                    Task::Expr(Expr::Val(a))  => tasks.push(Task::Val(a)),
+                    Task::Val(Val::Expr(a))   => tasks.push(Task::Expr(a)),
+                    Task::Val(a)              => ints.push(value_to_i16(context, a.clone())?),
+                    Task::Faces(Val::Expr(a)) if matches!(**a, Expr::Op(..)) => tasks.extend([Task::Range, Task::Expr(a)]),
+                    Task::Faces(a)            => faces.push(Expr::Val(a.clone()).faces(context)?),
+                    Task::Range               => { let face = pop!(); faces.push(Faces::Range(face)) },
+                    Task::Expr(Expr::Op(opr)) => {
+                        tasks.push(Task::Apply(opr));
+                        match opr {
+                            Op::Neg(a)                                                    => tasks.push(Task::Val(a)),
+                            Op::Len(..)                                                   => (),
+                            Op::Add(a, b) | Op::Sub(a, b) | Op::Mul(a, b) | Op::Div(a, b) => tasks.extend([Task::Val(a), Task::Expr(b)]),
+                            Op::Cmp(c, b)                                                 => tasks.extend([Task::Val(c.val()), Task::Expr(b)]),
+                            Op::Dice(a, b, c)                                             => { if let Some(c) = c { tasks.push(Task::Val(c.val())) } tasks.extend([Task::Expr(b), Task::Faces(a)]) },
+                            Op::Pool(a, b, c)                                             => { if let Some(c) = c.keep() { tasks.push(Task::Val(c)) } tasks.extend([Task::Expr(b), Task::Faces(a)]) },
+                        } // match ..
+                    }, // => ..
+                    Task::Apply(opr) => {
+                        let int = match opr {
+                            Op::Neg(..)           => Op::checked(pop!().checked_neg())?,
+                            Op::Len(a)            => Expr::Val(a.clone()).items(context)?.len() as i16,
+                            Op::Add(..)           => { let a = pop!(); let b = pop!(); Op::checked(b.checked_add(a))? },
+                            Op::Sub(..)           => { let a = pop!(); let b = pop!(); Op::checked(b.checked_sub(a))? },
+                            Op::Mul(..)           => { let a = pop!(); let b = pop!(); Op::checked(b.checked_mul(a))? },
+                            Op::Div(..)           => { let a = pop!(); let b = pop!(); Op::divide(b, a)? },
+                            Op::Cmp(c, _)         => { let a = pop!(); let b = pop!(); i16::from(match c {
+                                CmpTo::Eq(..) => b == a,
+                                CmpTo::Ne(..) => b != a,
+                                CmpTo::Gt(..) => b >= a,
+                                CmpTo::Ge(..) => b >  a,
+                                CmpTo::Lt(..) => b <  a,
+                                CmpTo::Le(..) => b <= a,
+                            })}, // => ..
+                            Op::Dice(_, _, cmp)   => {
+                                let c = match cmp { Some(_) => pop!(), None => 0i16 };
+                                let n = pop!();
+                                faces.pop().unwrap_or(Faces::Range(0i16)).sum(context, n, cmp.as_ref().map(|cmp| (cmp, c)))?
+                            }, // => ..
+                            Op::Pool(_, _, pool)  => {
+                                let keep = pool.keep().map(|_| pop!());
+                                let n    = pop!();
+                                pool.throw(context, &faces.pop().unwrap_or(Faces::Range(0i16)), n, keep)?
+                            }, // => ..
+                        }; // let ..
+                        ints.push(int);
+                    }, // => ..
+                } // match ..
+            } // while ..
+
+            Ok(pop!())
         } // fn ..
 
 
@@ -193,6 +363,15 @@
                     Val::Bool(bool)    => Ok(bool),
                     Val::Expr(expr)    => expr.as_bool(context),
                     Val::Ref(_, name)  => { let value = context.reg(&name)?.clone().as_val(context)?; value_to_bool(context, value) },
+                    Val::Proc(proc)    => { let value = proc.call(context, Vec::default())?; value_to_bool(context, value) },
+                    Val::Table(table)  => { let value = table.roll(context)?; value_to_bool(context, value) },
+                    Val::Text(text)    => Ok(!text.is_empty()),
+                    Val::Field(..)     |
+                    Val::Call(..)      |
+                    Val::Ask(..)       |
+                    Val::Lambda(..)    => { let value = Expr::Val(value).as_val(context)?; value_to_bool(context, value) },
+                    Val::List(..)      => Ok(!Expr::Val(value).items(context)?.is_empty()),
+                    Val::Record(..)    => Ok(!Expr::Val(value).fields(context)?.is_empty()),
                     Val::Void          => Ok(false),
                 } // match ..
             } // fn ..
@@ -200,56 +379,383 @@
 
             match self {
                 Expr::Val(value) => value_to_bool(context, value.clone()),
-                Expr::Op(op) => Ok(match op {
-                    Op::Neg(a)               => !Expr::Val(a.clone()).as_bool(context)?,
-                    Op::Add(a, b)            => b.as_bool(context)? |  Expr::Val(a.clone()).as_bool(context)?,
-                    Op::Sub(a, b)            => b.as_bool(context)? & !Expr::Val(a.clone()).as_bool(context)?,
-                    Op::Mul(a, b)            => b.as_bool(context)? &  Expr::Val(a.clone()).as_bool(context)?,
-                    Op::Div(_, b)            => b.as_bool(context)?,
-                    Op::Dice(_, b, _)        => b.as_bool(context)?,
-                    Op::Cmp(CmpTo::Eq(a), b) => b.as_bool(context)? == Expr::Val(a.clone()).as_bool(context)?,
-                    Op::Cmp(CmpTo::Ne(a), b) => b.as_bool(context)? != Expr::Val(a.clone()).as_bool(context)?,
-                    Op::Cmp(CmpTo::Gt(a), b) => b.as_int(context)?  >= Expr::Val(a.clone()).as_int(context)?,
-                    Op::Cmp(CmpTo::Ge(a), b) => b.as_int(context)?  >  Expr::Val(a.clone()).as_int(context)?,
-                    Op::Cmp(CmpTo::Lt(a), b) => b.as_int(context)?  <  Expr::Val(a.clone()).as_int(context)?,
-                    Op::Cmp(CmpTo::Le(a), b) => b.as_int(context)?  <= Expr::Val(a.clone()).as_int(context)?,
-                }), // => ..
+                Expr::Op(..)     => Ok(self.as_int(context)? != 0i16),
             } // match ..
         } // fn ..
         
 
-        /// Gets a dice throw output from an expression.
-        fn dice_throw(
+        /// Gets the items of a list output from an expression.
+        pub(crate) fn items(
             &self,
             context: &mut SyntaxContext,
-            face:    Expr,
-            cmp:     Option<CmpTo>,
-        ) -> Result<i16, InterpreterErr> {
-    
-            let face    = face.as_int(context)?;
-            let n       = self.as_int(context)?;
-    
-    
-            (0i16..n).map(|_| {
-                let dice = context.rand_bit_field.generate_irange(1i16..=face);
-                match cmp.clone() {
-                    Some(CmpTo::Eq(cmp)) => if dice == Expr::Val(cmp).as_int(context)? { Ok(1i16) } else { Ok(0i16) },
-                    Some(CmpTo::Ne(cmp)) => if dice != Expr::Val(cmp).as_int(context)? { Ok(1i16) } else { Ok(0i16) },
-                    Some(CmpTo::Gt(cmp)) => if dice >  Expr::Val(cmp).as_int(context)? { Ok(1i16) } else { Ok(0i16) },
-                    Some(CmpTo::Ge(cmp)) => if dice >= Expr::Val(cmp).as_int(context)? { Ok(1i16) } else { Ok(0i16) },
-                    Some(CmpTo::Lt(cmp)) => if dice <  Expr::Val(cmp).as_int(context)? { Ok(1i16) } else { Ok(0i16) },
-                    Some(CmpTo::Le(cmp)) => if dice <= Expr::Val(cmp).as_int(context)? { Ok(1i16) } else { Ok(0i16) },
-                    None                 => Ok(dice),
+        ) -> Result<Vec<Primitive>, InterpreterErr> {
+            match self.as_val(context)?.as_list(context)? {
+                Val::List(items) => items.iter().map(|item| item.as_val(context)?.as_primitive(context)).collect(),
+                _                => Err(InterpreterErr::NotAList),
+            } // match ..
+        } // fn ..
+
+
+        /// Gets the fields of a record output from an expression.
+        pub(crate) fn fields(
+            &self,
+            context: &mut SyntaxContext,
+        ) -> Result<Vec<(Symbol, Primitive)>, InterpreterErr> {
+            match self.as_val(context)?.as_record(context)? {
+                Val::Record(fields) => fields.iter().map(|(name, field)| Ok((*name, field.as_val(context)?.as_primitive(context)?))).collect(),
+                _                   => Err(InterpreterErr::NotARecord),
+            } // match ..
+        } // fn ..
+
+
+        /// Gets the faces of a die out of an expression, a list standing for a custom die.
+        fn faces(&self, context: &mut SyntaxContext) -> Result<Faces, InterpreterErr> {
+            match self.as_val(context)? {
+                Val::List(items) => Ok(Faces::List(items.iter().map(|item| item.as_int(context)).collect::<Result<Vec<i16>, InterpreterErr>>()?)),
+                face             => Ok(Faces::Range(Expr::Val(face).as_int(context)?)),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Drop for Expr {
+        /// Drops nested operands one at a time, so that long chains of operators don't overflow the stack.
+        fn drop(&mut self) {
+
+            fn take(expr: &mut Expr, exprs: &mut Vec<Expr>) {
+
+                fn take_val(val: &mut Val, exprs: &mut Vec<Expr>) {
+                    if let Val::Expr(b) = val { exprs.push(std::mem::replace(&mut **b, Expr::Val(Val::Void))) }
+                } // fn ..
+
+                match expr {
+                    Expr::Val(a)  => take_val(a, exprs),
+                    Expr::Op(opr) => {
+                        let (a, b) = match opr {
+                            Op::Neg(a)    | Op::Len(a)                                    => (a, None),
+                            Op::Add(a, b) | Op::Sub(a, b) | Op::Mul(a, b) | Op::Div(a, b) => (a, Some(b)),
+                            Op::Dice(a, b, c)                                             => { if let Some(c) = c { take_val(c.val_mut(), exprs) } (a, Some(b)) },
+                            Op::Pool(a, b, _)                                             => (a, Some(b)),
+                            Op::Cmp(c, b)                                                 => (c.val_mut(), Some(b)),
+                        }; // let ..
+
+                        take_val(a, exprs);
+                        if let Some(b) = b { exprs.push(std::mem::replace(&mut **b, Expr::Val(Val::Void))) }
+                    }, // => ..
+                } // match ..
+            } // fn ..
+
+
+            let mut exprs = Vec::default();
+            take(self, &mut exprs);
+            while let Some(mut expr) = exprs.pop() { take(&mut expr, &mut exprs) }
+        } // fn ..
+    } // impl ..
+
+
+    impl Serialize for Expr {
+        /// Writes the expression, growing the stack as needed so that long chains of operators don't overflow it.
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            stacker::maybe_grow(RED_ZONE, STACK_SIZE, || ExprDef::serialize(self, serializer))
+        } // fn ..
+    } // impl ..
+
+    impl<'de> Deserialize<'de> for Expr {
+        /// Reads an expression, growing the stack as needed so that long chains of operators don't overflow it.
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            stacker::maybe_grow(RED_ZONE, STACK_SIZE, || ExprDef::deserialize(deserializer))
+        } // fn ..
+    } // impl ..
+
+
+    impl Macro {
+        /// Expands the macro, each parameter read in its body being replaced by the matching argument read as the type it is read as.
+        pub(crate) fn expand(&self, index: usize, args: Vec<Expr>) -> Result<Expr, InterpreterErr> {
+
+            /// Replaces the parameters read along a chain of operations.
+            fn expr_args(expr: &mut Expr, args: &[(Symbol, Expr)], index: usize) -> Result<(), InterpreterErr> {
+                let mut expr = expr;
+                loop {
+                    expr = match expr {
+                        Expr::Val(a)  => return val_args(a, args, index),
+                        Expr::Op(opr) => match opr {
+                            Op::Neg(a)    | Op::Len(a)                                    => return val_args(a, args, index),
+                            Op::Cmp(c, b)                                                 => { val_args(c.val_mut(), args, index)?; b },
+                            Op::Add(a, b) | Op::Sub(a, b) | Op::Mul(a, b) | Op::Div(a, b) => { val_args(a, args, index)?; b },
+                            Op::Dice(a, b, c)                                             => { val_args(a, args, index)?; if let Some(c) = c { val_args(c.val_mut(), args, index)? } b },
+                            Op::Pool(a, b, c)                                             => { val_args(a, args, index)?; if let Some(Keep::High(c) | Keep::Low(c)) = &mut c.keep { val_args(c, args, index)? } b },
+                        }, // => ..
+                    }; // expr ..
+                } // loop ..
+            } // fn ..
+
+            /// Replaces the parameters read in a value.
+            fn val_args(a: &mut Val, args: &[(Symbol, Expr)], index: usize) -> Result<(), InterpreterErr> {
+
+                let arg = |name: &Symbol| args.iter().find(|(param, _)| param == name).map(|(_, arg)| arg);
+                match a {
+                    Val::Ref(id, name) => if let Some(arg) = arg(name) {
+                        let value = typed(index, id, arg)?;
+                        *a = value;
+                    }, // => ..
+                    Val::Field(id, name, path) => if let Some(arg) = arg(name) {
+                        *a = match arg {
+                            Expr::Val(Val::Ref(_, record))         => Val::Field(id.clone(), *record, path.clone()),
+                            Expr::Val(Val::Field(_, record, outer)) => Val::Field(id.clone(), *record, outer.iter().chain(path.iter()).copied().collect()),
+                            arg                                     => return Err(InterpreterErr::MismatchedType(index, PrimitiveId::Record, static_id(arg).unwrap_or(PrimitiveId::Void))),
+                        }; // match ..
+                    }, // => ..
+                    Val::Call(_, name, items) => {
+                        for item in items.iter_mut() { expr_args(item, args, index)? }
+                        match arg(name) {
+                            Some(Expr::Val(Val::Ref(_, callee))) => *name = *callee,
+                            Some(arg)                            => return Err(InterpreterErr::MismatchedType(index, PrimitiveId::Proc, static_id(arg).unwrap_or(PrimitiveId::Void))),
+                            None                                 => (),
+                        } // match ..
+                    }, // => ..
+                    Val::List(items)          => for item in items { expr_args(item, args, index)? },
+                    Val::Record(fields)       => for (_, field) in fields { expr_args(field, args, index)? },
+                    Val::Expr(b)              => expr_args(b, args, index)?,
+                    Val::Lambda(params, body) => decl_args(body, &shadow(args, params), index)?,
+                    Val::Proc(..) | Val::Table(..)                                           => (),
+                    Val::Int(..)  | Val::Bool(..) | Val::Text(..) | Val::Ask(..) | Val::Void => (),
+                } // match ..
+                Ok(())
+            } // fn ..
+
+            /// Replaces the parameters read in a declaration, down to the registers its statements shadow them with.
+            fn decl_args(decl: &mut Decl, args: &[(Symbol, Expr)], index: usize) -> Result<(), InterpreterErr> {
+
+                let stms = match decl {
+                    Decl::Expr(expr)    => return expr_args(expr, args, index),
+                    Decl::Closure(stms) => stms,
+                }; // let ..
+
+                let mut args = args.to_vec();
+                for stm in stms {
+                    match stm {
+                        Stm::RegLet(_, _, decl) | Stm::RegSet(_, _, decl) | Stm::Append(_, _, decl) | Stm::FieldSet(_, _, _, decl) => decl_args(decl, &args, index)?,
+                        Stm::Remove(_, _, expr) | Stm::Tell(_, expr, _)   | Stm::Assert(_, expr)    | Stm::Out(_, expr)            => expr_args(expr, &args, index)?,
+                        Stm::Define(_, _, params, decl)                                                                         => decl_args(decl, &shadow(&args, params), index)?,
+                        Stm::Macro(_, _, params, body)                                                                          => expr_args(body, &shadow(&args, params), index)?,
+                        Stm::ItemSet(_, _, at, decl)                                                                            => { expr_args(at, &args, index)?; decl_args(decl, &args, index)? },
+                        Stm::If(_, expr, decl)  | Stm::While(_, expr, decl)                                                     => { expr_args(expr, &args, index)?; decl_args(decl, &args, index)? },
+                        Stm::ForEach(_, _, name, expr, decl)                                                                    => { expr_args(expr, &args, index)?; decl_args(decl, &shadow(&args, &[*name]), index)? },
+                        Stm::Never(_, sample)                                                                                   => { expr_args(&mut sample.runs, &args, index)?; expr_args(&mut sample.expr, &args, index)? },
+                        Stm::Table(_, _, table) => {
+                            expr_args(&mut table.dice, &args, index)?;
+                            for (_, _, decl) in &mut table.rows { decl_args(decl, &args, index)? }
+                        }, // => ..
+                        Stm::For(_, name, bounds, decl) => {
+                            for expr in [&mut bounds.from, &mut bounds.to].into_iter().chain(bounds.step.as_mut()) { expr_args(expr, &args, index)? }
+                            decl_args(decl, &shadow(&args, &[*name]), index)?;
+                        }, // => ..
+                        Stm::Mean(_, sample, target, tolerance) => {
+                            expr_args(&mut sample.runs, &args, index)?;
+                            expr_args(&mut sample.expr, &args, index)?;
+                            for bound in [target, tolerance] { if let Bound::Expr(expr) = bound { expr_args(expr, &args, index)? } }
+                        }, // => ..
+                    } // match ..
+
+                    if let Stm::RegLet(_, name, _) | Stm::Define(_, name, ..) | Stm::Table(_, name, _) = stm { args.retain(|(param, _)| param != name) }
+                } // for ..
+                Ok(())
+            } // fn ..
+
+            /// Returns the arguments of the parameters that some names don't shadow.
+            fn shadow(args: &[(Symbol, Expr)], names: &[Symbol]) -> Vec<(Symbol, Expr)> {
+                args.iter().filter(|(param, _)| !names.contains(param)).cloned().collect()
+            } // fn ..
+
+            /// Returns an argument read as a given type, references being read as that type and other values having to be of it.
+            fn typed(index: usize, id: &PrimitiveId, arg: &Expr) -> Result<Val, InterpreterErr> {
+                match arg {
+                    Expr::Val(Val::Ref(_, name))          => Ok(Val::Ref(id.clone(), *name)),
+                    Expr::Val(Val::Field(_, name, path))  => Ok(Val::Field(id.clone(), *name, path.clone())),
+                    Expr::Val(Val::Call(_, name, items))  => Ok(Val::Call(id.clone(), *name, items.clone())),
+                    arg                                   => match static_id(arg) {
+                        Some(found) if found != *id => Err(InterpreterErr::MismatchedType(index, id.clone(), found)),
+                        _                           => Ok(Val::Expr(Box::new(arg.clone()))),
+                    }, // => ..
+                } // match ..
+            } // fn ..
+
+            /// Returns the type of an argument when it can be told without running it, sums of booleans remaining booleans.
+            fn static_id(arg: &Expr) -> Option<PrimitiveId> {
+                match arg {
+                    Expr::Val(val) => match val {
+                        Val::Int(..)                                                              => Some(PrimitiveId::Int),
+                        Val::Bool(..)                                                             => Some(PrimitiveId::Bool),
+                        Val::Text(..)                                                             => Some(PrimitiveId::Text),
+                        Val::List(..)                                                             => Some(PrimitiveId::List),
+                        Val::Table(..)                                                            => Some(PrimitiveId::Table),
+                        Val::Record(..)                                                           => Some(PrimitiveId::Record),
+                        Val::Lambda(..) | Val::Proc(..)                                           => Some(PrimitiveId::Proc),
+                        Val::Void                                                                 => Some(PrimitiveId::Void),
+                        Val::Ref(id, ..) | Val::Field(id, ..) | Val::Call(id, ..) | Val::Ask(id, ..) => Some(id.clone()),
+                        Val::Expr(expr)                                                           => static_id(expr),
+                    }, // => ..
+                    Expr::Op(Op::Cmp(..))                                                 => Some(PrimitiveId::Bool),
+                    Expr::Op(Op::Len(..) | Op::Mul(..) | Op::Div(..) | Op::Dice(..) | Op::Pool(..)) => Some(PrimitiveId::Int),
+                    Expr::Op(Op::Neg(..) | Op::Add(..) | Op::Sub(..))                     => None,
                 } // match ..
-            }).sum()
+            } // fn ..
+
+
+            if args.len() != self.params.len() { return Err(InterpreterErr::WrongArity(self.params.len(), args.len())) }
+
+            let args     = self.params.iter().copied().zip(args).collect::<Vec<(Symbol, Expr)>>();
+            let mut body = self.body.clone();
+            expr_args(&mut body, &args, index)?;
+            Ok(body)
+        } // fn ..
+    } // impl ..
+
+
+    impl Op {
+        /// Unwraps the result of an integer operation, failing when it overflowed.
+        pub(crate) fn checked(int: Option<i16>) -> Result<i16, InterpreterErr> { int.ok_or(InterpreterErr::Overflow) }
+
+
+        /// Divides an integer by another, failing on a division by zero or an overflow.
+        pub(crate) fn divide(b: i16, a: i16) -> Result<i16, InterpreterErr> {
+            match a {
+                0i16 => Err(InterpreterErr::DivisionByZero),
+                a    => Op::checked(b.checked_div(a)),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Faces {
+        /// Throws the die once, a die without faces landing on zero.
+        pub(crate) fn throw(&self, context: &mut SyntaxContext) -> i16 {
+            let die = match self {
+                Faces::Range(face) => context.rng.generate_irange(1i16..=*face),
+                Faces::List(faces) => {
+                    let index = context.rng.generate_irange(0i16..=(faces.len() as i16).saturating_sub(1i16));
+                    faces.get(index as usize).copied().unwrap_or_default()
+                }, // => ..
+            }; // let ..
+            context.tally_die(die);
+            die
+        } // fn ..
+
+
+        /// Throws `n` dice, counting the ones passing an eventual comparison or else summing them up.
+        pub(crate) fn sum(
+            &self,
+            context: &mut SyntaxContext,
+            n:       i16,
+            cmp:     Option<(&CmpTo, i16)>,
+        ) -> Result<i16, InterpreterErr> {
+
+            context.throw_dice(n)?;
+            (0i16..n).try_fold(0i16, |sum, _| {
+                let dice = self.throw(context);
+                Op::checked(sum.checked_add(match cmp {
+                    Some((CmpTo::Eq(..), c)) => i16::from(dice == c),
+                    Some((CmpTo::Ne(..), c)) => i16::from(dice != c),
+                    Some((CmpTo::Gt(..), c)) => i16::from(dice >  c),
+                    Some((CmpTo::Ge(..), c)) => i16::from(dice >= c),
+                    Some((CmpTo::Lt(..), c)) => i16::from(dice <  c),
+                    Some((CmpTo::Le(..), c)) => i16::from(dice <= c),
+                    None                     => dice,
+                })) // checked()
+            }) // try_fold()
+        } // fn ..
+
+
+        /// Returns the face a die explodes on, a die always landing on the same face never exploding.
+        pub(crate) fn highest(&self) -> Option<i16> {
+            match self {
+                Faces::Range(face) => (*face > 1i16).then_some(*face),
+                Faces::List(faces) => {
+                    let (min, max) = (faces.iter().min()?, faces.iter().max()?);
+                    (max > min).then_some(*max)
+                }, // => ..
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Pool {
+        /// Throws `n` dice with given faces, each exploding die being rolled again and added up, then sums up the kept ones.
+        pub(crate) fn throw(
+            &self,
+            context: &mut SyntaxContext,
+            faces:   &Faces,
+            n:       i16,
+            keep:    Option<i16>,
+        ) -> Result<i16, InterpreterErr> {
+
+            let highest = if self.explode { faces.highest() } else { None };
+            context.throw_dice(n)?;
+            let mut dice = (0i16..n).map(|_| {
+                let mut total = 0i16;
+                loop {
+                    let dice = faces.throw(context);
+                    total    = Op::checked(total.checked_add(dice))?;
+                    if highest != Some(dice) { break Ok(total) }
+                    context.throw_dice(1i16)?;
+                } // loop ..
+            }).collect::<Result<Vec<i16>, InterpreterErr>>()?;
+
+            match &self.keep {
+                Some(Keep::High(_)) => dice.sort_unstable_by(|a, b| b.cmp(a)),
+                Some(Keep::Low(_))  => dice.sort_unstable(),
+                None                => (),
+            } // match ..
+
+            let kept = keep.map_or(dice.len(), |keep| keep.max(0i16) as usize);
+            dice.into_iter().take(kept).try_fold(0i16, |sum, dice| Op::checked(sum.checked_add(dice)))
+        } // fn ..
+
+
+        /// Returns the value counting the kept dice.
+        pub(crate) fn keep(&self) -> Option<&Val> {
+            match &self.keep {
+                Some(Keep::High(keep) | Keep::Low(keep)) => Some(keep),
+                None                                     => None,
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl CmpTo {
+        /// Returns the value compared to.
+        pub(crate) fn val(&self) -> &Val {
+            match self {
+                CmpTo::Eq(val) | CmpTo::Ne(val) | CmpTo::Gt(val) | CmpTo::Ge(val) | CmpTo::Lt(val) | CmpTo::Le(val) => val,
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the mutable value compared to.
+        pub(crate) fn val_mut(&mut self) -> &mut Val {
+            match self {
+                CmpTo::Eq(val) | CmpTo::Ne(val) | CmpTo::Gt(val) | CmpTo::Ge(val) | CmpTo::Lt(val) | CmpTo::Le(val) => val,
+            } // match ..
         } // fn ..
     } // impl ..
 
 
     impl Val {
+        /// Reads a primitive as a value of the given type, calling or rolling it when it is a procedure or a table and none is expected.
+        pub(crate) fn typed(context: &mut SyntaxContext, id: &PrimitiveId, primitive: Primitive) -> Result<Self, InterpreterErr> {
+            match (id, primitive) {
+                (PrimitiveId::Proc,  Primitive::Proc(proc))   => Ok(Val::Proc(Box::new(proc))),
+                (PrimitiveId::Proc,  _)                       => Err(InterpreterErr::NotAProc),
+                (PrimitiveId::Table, Primitive::Table(table)) => Ok(Val::Table(Box::new(table))),
+                (PrimitiveId::Table, _)                       => Err(InterpreterErr::NotATable),
+                (id, primitive)                               => primitive.as_val(context)?.cast(context, id),
+            } // match ..
+        } // fn ..
+
+
         pub(crate) fn unwraped(&self, context: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
             match self {
-                Val::Ref(_, name) => Ok(context.reg(&name)?.clone().as_val(context)?),
+                Val::Ref(_, name) => Ok(context.reg(name)?.clone().as_val(context)?),
                 _                 => Ok(self.clone()),
             } // match ..
         } // fn ..
@@ -259,20 +765,53 @@
             match self {
                 Val::Int(int)     => Ok(Primitive::Int(*int)),
                 Val::Bool(bool)   => Ok(Primitive::Bool(*bool)),
+                Val::Text(text)   => Ok(Primitive::Text(text.clone())),
+                Val::Table(table) => Ok(Primitive::Table(*table.clone())),
                 Val::Expr(expr)   => Ok(expr.as_val(context)?.as_primitive(context)?),
-                Val::Ref(_, name) => Ok(context.reg(&name)?.clone()),
+                Val::Ref(_, name) => Ok(context.reg(name)?.clone()),
+                Val::Proc(proc)   => Ok(Primitive::Proc(*proc.clone())),
+                Val::List(items)  => Ok(Primitive::List(items.iter().map(|item| item.as_val(context)?.as_primitive(context)).collect::<Result<Vec<Primitive>, InterpreterErr>>()?)),
+                Val::Record(..)   => Ok(Primitive::Record(Record { fields: Expr::Val(self.clone()).fields(context)? })),
+                Val::Field(..)    |
+                Val::Call(..)     |
+                Val::Ask(..)      |
+                Val::Lambda(..)   => Ok(Expr::Val(self.clone()).as_val(context)?.as_primitive(context)?),
                 Val::Void         => Ok(Primitive::Void),
             } // match ..
         } // fn ..
 
 
+        /// Casts the value to the given primitive type.
+        fn cast(&self, context: &mut SyntaxContext, id: &PrimitiveId) -> Result<Self, InterpreterErr> {
+            match id {
+                PrimitiveId::Int    => self.as_int(context),
+                PrimitiveId::Bool   => self.as_bool(context),
+                PrimitiveId::Text   => self.as_text(context),
+                PrimitiveId::Void   => self.as_void(context),
+                PrimitiveId::Proc   => self.as_proc(context),
+                PrimitiveId::List   => self.as_list(context),
+                PrimitiveId::Table  => self.as_table(context),
+                PrimitiveId::Record => self.as_record(context),
+            } // match ..
+        } // fn ..
+
+
 
         fn as_int(&self, context: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
             match self {
                 Val::Int(int)     => Ok(Val::Int(*int)),
                 Val::Bool(bool)   => Ok(Val::Int(if *bool { 1i16 } else { 0i16 })),
                 Val::Expr(expr)   => Ok(expr.as_val(context)?.as_int(context)?),
-                Val::Ref(_, name) => Ok(context.reg(&name)?.clone().as_val(context)?.as_int(context)?),
+                Val::Ref(_, name) => Ok(context.reg(name)?.clone().as_val(context)?.as_int(context)?),
+                Val::Proc(proc)   => Ok(proc.call(context, Vec::default())?.as_int(context)?),
+                Val::Table(table) => Ok(table.roll(context)?.as_int(context)?),
+                Val::Text(..)     => Ok(Val::Int(0i16)),
+                Val::Field(..)    |
+                Val::Call(..)     |
+                Val::Ask(..)      |
+                Val::Lambda(..)   => Ok(Expr::Val(self.clone()).as_val(context)?.as_int(context)?),
+                Val::List(..)     => Ok(Val::Int(Expr::Val(self.clone()).items(context)?.len() as i16)),
+                Val::Record(..)   => Ok(Val::Int(Expr::Val(self.clone()).fields(context)?.len() as i16)),
                 Val::Void         => Ok(Val::Int(0i16)),
             } // match ..
         } // fn ..
@@ -283,24 +822,205 @@
                 Val::Int(int)     => Ok(Val::Bool(*int != 0i16)),
                 Val::Bool(bool)   => Ok(Val::Bool(*bool)),
                 Val::Expr(expr)   => Ok(expr.as_val(context)?.as_bool(context)?),
-                Val::Ref(_, name) => Ok(context.reg(&name)?.clone().as_val(context)?.as_bool(context)?),
+                Val::Ref(_, name) => Ok(context.reg(name)?.clone().as_val(context)?.as_bool(context)?),
+                Val::Proc(proc)   => Ok(proc.call(context, Vec::default())?.as_bool(context)?),
+                Val::Table(table) => Ok(table.roll(context)?.as_bool(context)?),
+                Val::Text(text)   => Ok(Val::Bool(!text.is_empty())),
+                Val::Field(..)    |
+                Val::Call(..)     |
+                Val::Ask(..)      |
+                Val::Lambda(..)   => Ok(Expr::Val(self.clone()).as_val(context)?.as_bool(context)?),
+                Val::List(..)     => Ok(Val::Bool(!Expr::Val(self.clone()).items(context)?.is_empty())),
+                Val::Record(..)   => Ok(Val::Bool(!Expr::Val(self.clone()).fields(context)?.is_empty())),
                 Val::Void         => Ok(Val::Bool(false)),
             } // match ..
         } // fn ..
 
 
+        fn as_text(&self, context: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
+            match self {
+                Val::Int(int)     => Ok(Val::Text(int.to_string())),
+                Val::Bool(bool)   => Ok(Val::Text(bool.to_string())),
+                Val::Text(..)     => Ok(self.clone()),
+                Val::Expr(expr)   => Ok(expr.as_val(context)?.as_text(context)?),
+                Val::Ref(_, name) => Ok(context.reg(name)?.clone().as_val(context)?.as_text(context)?),
+                Val::Proc(proc)   => Ok(proc.call(context, Vec::default())?.as_text(context)?),
+                Val::Table(table) => Ok(table.roll(context)?.as_text(context)?),
+                Val::Field(..)    |
+                Val::Call(..)     |
+                Val::Ask(..)      |
+                Val::Lambda(..)   => Ok(Expr::Val(self.clone()).as_val(context)?.as_text(context)?),
+                Val::List(..)     |
+                Val::Record(..)   => Err(InterpreterErr::NotAText),
+                Val::Void         => Ok(Val::Text(String::default())),
+            } // match ..
+        } // fn ..
+
+
         fn as_void(&self, _: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
+            Ok(Val::Void)
+        } // fn ..
+
+
+        fn as_proc(&self, context: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
+            match self {
+                Val::Proc(..)   => Ok(self.clone()),
+                Val::Expr(expr) => expr.as_val(context)?.as_proc(context),
+                _               => Err(InterpreterErr::NotAProc),
+            } // match ..
+        } // fn ..
+
+
+        fn as_list(&self, context: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
+            match self {
+                Val::List(..)   => Ok(self.clone()),
+                Val::Expr(expr) => expr.as_val(context)?.as_list(context),
+                _               => Err(InterpreterErr::NotAList),
+            } // match ..
+        } // fn ..
+
+
+        fn as_table(&self, context: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
+            match self {
+                Val::Table(..)  => Ok(self.clone()),
+                Val::Expr(expr) => expr.as_val(context)?.as_table(context),
+                _               => Err(InterpreterErr::NotATable),
+            } // match ..
+        } // fn ..
+
+
+        fn as_record(&self, context: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
+            match self {
+                Val::Record(..) => Ok(self.clone()),
+                Val::Expr(expr) => expr.as_val(context)?.as_record(context),
+                _               => Err(InterpreterErr::NotARecord),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl fmt::Display for Expr {
+        /// Writes the expression back as source code, operands in the order they were written.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Expr::Val(val)                    => write!(f, "{}", val),
+                Expr::Op(Op::Neg(a))              => write!(f, "-{}", a),
+                Expr::Op(Op::Len(a))              => write!(f, "length {}", a),
+                Expr::Op(Op::Add(a, b))           => write!(f, "{} + {}", b, a),
+                Expr::Op(Op::Sub(a, b))           => write!(f, "{} - {}", b, a),
+                Expr::Op(Op::Mul(a, b))           => write!(f, "{} * {}", b, a),
+                Expr::Op(Op::Div(a, b))           => write!(f, "{} / {}", b, a),
+                Expr::Op(Op::Dice(a, b, None))    => write!(f, "{} d {}", b, a),
+                Expr::Op(Op::Dice(a, b, Some(c))) => write!(f, "{} d {} : {}", b, a, c),
+                Expr::Op(Op::Pool(a, b, c))       => write!(f, "{} d {}{}", b, a, c),
+                Expr::Op(Op::Cmp(c, b))           => write!(f, "{} {}", b, c),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl fmt::Display for CmpTo {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             match self {
-                Val::Int(..)  => Ok(Val::Void),
-                Val::Bool(..) => Ok(Val::Void),
-                Val::Expr(..) => Ok(Val::Void),
-                Val::Ref(..)  => Ok(Val::Void),
-                Val::Void     => Ok(Val::Void),
+                CmpTo::Eq(val) => write!(f, "= {}", val),
+                CmpTo::Ne(val) => write!(f, "/= {}", val),
+                CmpTo::Gt(val) => write!(f, "> {}", val),
+                CmpTo::Ge(val) => write!(f, ">= {}", val),
+                CmpTo::Lt(val) => write!(f, "< {}", val),
+                CmpTo::Le(val) => write!(f, "<= {}", val),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl fmt::Display for Pool {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.explode { write!(f, "!")?; }
+            match &self.keep {
+                Some(Keep::High(keep)) => write!(f, " kh {}", keep),
+                Some(Keep::Low(keep))  => write!(f, " kl {}", keep),
+                None                   => Ok(()),
             } // match ..
         } // fn ..
+    } // impl ..
+
 
+    impl fmt::Display for Val {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+
+            fn list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: impl Iterator<Item = T>) -> fmt::Result {
+                for (i, item) in items.enumerate() { write!(f, "{}{}", if i == 0usize { "" } else { ", " }, item)?; }
+                Ok(())
+            } // fn ..
 
-        fn as_proc(&self, _: &mut SyntaxContext) -> Result<Self, InterpreterErr> {
-            Ok(Val::Expr(Box::new(Expr::Val(self.clone()))))
+            match self {
+                Val::Int(int)              => write!(f, "{}", int),
+                Val::Bool(bool)            => write!(f, "{}", bool),
+                Val::Text(text)            => write!(f, "'{}'", text),
+                Val::Ref(id, name)         => write!(f, "{} [{}]", id, name),
+                Val::Field(id, name, path) => { write!(f, "{} [{}", id, name)?; path.iter().try_for_each(|field| write!(f, ".{}", field))?; write!(f, "]") },
+                Val::Call(id, name, args)  => { write!(f, "{} [{}](", id, name)?; list(f, args.iter())?; write!(f, ")") },
+                Val::Lambda(params, _)     => { write!(f, "proc(")?; list(f, params.iter().map(|param| format!("[{}]", param)))?; write!(f, "): ...")  },
+                Val::Proc(_)               => write!(f, "proc"),
+                Val::List(items)           => { write!(f, "list(")?; list(f, items.iter())?; write!(f, ")") },
+                Val::Table(_)              => write!(f, "table"),
+                Val::Record(fields)        => { write!(f, "record(")?; list(f, fields.iter().map(|(name, field)| format!("[{}] <- {}", name, field)))?; write!(f, ")") },
+                Val::Ask(id, text, range)  => { write!(f, "ask {} '{}'", id, text)?; range.map_or(Ok(()), |(from, to)| write!(f, " in {} .. {}", from, to)) },
+                Val::Expr(expr)            => write!(f, "({})", expr),
+                Val::Void                  => write!(f, "..."),
+            } // match ..
         } // fn ..
     } // impl ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{SyntaxContext, SyntaxElement, TypeChecker, Program, Stm, Decl, WsToken, Primitive, InterpreterErr};
+
+        #[test]
+        fn deep_chains() {
+            for (chain, n, expected) in [("1 + ", 10_000usize, 10_001i16), ("0 + ", 100_000usize, 1i16), ("1 * ", 100_000usize, 1i16), ("2 / ", 100_000usize, 1i16), ("- ", 100_000usize, 1i16), ("1 d ", 100_000usize, 1i16), ("- 1 * ", 100_000usize, 1i16)] {
+
+                let src         = format!("let [x] <- {}1;", chain.repeat(n));
+                let mut context = SyntaxContext::default();
+                context.push(&src).unwrap();
+
+                let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+                TypeChecker::new(&context).check(&stms).unwrap();
+
+                let mut tree = context.clone();
+                Program::compile(&context, &stms).run(&mut context).unwrap();
+                Stm::run(&mut tree, &Decl::Closure(stms)).unwrap();
+
+                assert_eq!(context.get_reg("x"), Some(&Primitive::Int(expected)), "`{}` chain", chain);
+                assert_eq!(tree.get_reg("x"),    Some(&Primitive::Int(expected)), "`{}` chain", chain);
+            } // for ..
+
+            let parens = |n: usize| format!("let [x] <- {}1{};", "(".repeat(n), ")".repeat(n));
+            let blocks = |n: usize| format!("let [x] <- 0; {}set [x] <- 1; {}", "if | 1 = 1 |: ".repeat(n), ">>>> ".repeat(n));
+            for src in [parens(36usize), blocks(36usize)] {
+                let mut context = SyntaxContext::default();
+                context.push(&src).unwrap();
+
+                let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+                TypeChecker::new(&context).check(&stms).unwrap();
+
+                let mut tree = context.clone();
+                Program::compile(&context, &stms).run(&mut context).unwrap();
+                Stm::run(&mut tree, &Decl::Closure(stms)).unwrap();
+
+                assert_eq!(context.get_reg("x"), Some(&Primitive::Int(1i16)));
+                assert_eq!(tree.get_reg("x"),    Some(&Primitive::Int(1i16)));
+            } // for ..
+
+            for src in [parens(10_000usize), blocks(10_000usize), format!("let [x] <- {}1;", "list (".repeat(10_000usize))] {
+                let mut context = SyntaxContext::default();
+                context.push(&src).unwrap();
+                assert!(matches!(Stm::scan(&mut context, Vec::default(), WsToken::Eof), Err(InterpreterErr::NestingExceeded(40usize))));
+            } // for ..
+        } // fn ..
+    } // mod ..
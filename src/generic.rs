@@ -2,10 +2,19 @@
 // D E P E N D E N C I E S
 //#########################
 
+    use std::fmt;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::hash::{Hash, Hasher};
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
     use crate::{
         WsToken,
         InterpreterErr,
-        Val, Decl, Stm, SyntaxContext,
+        Val, Expr, Op, Decl, Stm, SyntaxContext,
+        Symbol,
     }; // use ..
 
 
@@ -13,20 +22,76 @@
 // D E F I N I T I O N S
 //#######################
 
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)]
+    /// A value stored in a register.
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
     pub enum Primitive {
         Int(i16),
         Bool(bool),
-        Proc(Decl),
+        Text(String),
+        Proc(Proc),
+        List(Vec<Primitive>),
+        Table(Table),
+        Record(Record),
         Void,
     } // enum ..
 
 
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)]
+    /// A procedure along with the registers it captured when created, its body being shared by every copy of it.
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    pub struct Proc {
+        pub(crate) params:   Vec<Symbol>,
+        pub(crate) captures: Vec<(Symbol, Capture)>,
+        pub(crate) body:     Arc<Decl>,
+    } // struct ..
+
+
+    /// The value of a captured register, shared by every copy of the procedure so that it keeps what its calls write.
+    #[derive(Clone)]
+    pub(crate) struct Capture(Arc<Mutex<Primitive>>);
+
+
+    /// The cells met while serialising or deserialising a value, so that a cell shared by several procedures is written once and read back shared.
+    #[derive(Default)]
+    struct Cells {
+        ids:   HashMap<usize, usize>,
+        cells: HashMap<usize, Capture>,
+    } // struct ..
+
+
+    /// Forgets the cells met once the outermost serialisation or deserialisation sharing them ends.
+    struct CellsGuard(bool);
+
+
+    thread_local! {
+        static CELLS: RefCell<Option<Cells>> = const { RefCell::new(None) };
+    } // thread_local ..
+
+
+    /// A random table mapping ranges of rolls to results.
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    pub struct Table {
+        pub(crate) dice: Expr,
+        pub(crate) rows: Vec<(i16, i16, Decl)>,
+    } // struct ..
+
+
+    /// A record of named fields, kept in the order they were declared.
+    #[derive(Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    pub struct Record {
+        pub(crate) fields: Vec<(Symbol, Primitive)>,
+    } // struct ..
+
+
+    /// The type of a value.
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
     pub enum PrimitiveId {
         Int,
         Bool,
+        Text,
         Proc,
+        List,
+        Table,
+        Record,
         Void,
     } // enum ..
 
@@ -41,20 +106,451 @@
             match value {
                 WsToken::Int  => Ok(PrimitiveId::Int),
                 WsToken::Bool => Ok(PrimitiveId::Bool),
+                WsToken::Str  => Ok(PrimitiveId::Text),
                 WsToken::Void => Ok(PrimitiveId::Void),
-                _             => Err(InterpreterErr::FailedToReadPrimitive),
+                WsToken::Proc => Ok(PrimitiveId::Proc),
+                WsToken::List   => Ok(PrimitiveId::List),
+                WsToken::Table  => Ok(PrimitiveId::Table),
+                WsToken::Record => Ok(PrimitiveId::Record),
+                _               => Err(InterpreterErr::FailedToReadPrimitive),
             } // match ..
         } // fn ..
     } // impl ..
 
 
     impl Primitive {
+        /// Returns the type of the value.
+        pub fn id(&self) -> PrimitiveId {
+            match self {
+                Primitive::Int(..)    => PrimitiveId::Int,
+                Primitive::Bool(..)   => PrimitiveId::Bool,
+                Primitive::Text(..)   => PrimitiveId::Text,
+                Primitive::Proc(..)   => PrimitiveId::Proc,
+                Primitive::List(..)   => PrimitiveId::List,
+                Primitive::Table(..)  => PrimitiveId::Table,
+                Primitive::Record(..) => PrimitiveId::Record,
+                Primitive::Void       => PrimitiveId::Void,
+            } // match ..
+        } // fn ..
+
+
         pub(crate) fn as_val(&self, context: &mut SyntaxContext) -> Result<Val, InterpreterErr> {
             match self {
-                Primitive::Int(int)   => Ok(Val::Int(*int)),
-                Primitive::Bool(bool) => Ok(Val::Bool(*bool)),
-                Primitive::Proc(decl) => Ok(Val::Expr(Box::new(Stm::run(context, decl)?.unwrap()))),
-                Primitive::Void       => Ok(Val::Void),
+                Primitive::Int(int)     => Ok(Val::Int(*int)),
+                Primitive::Bool(bool)   => Ok(Val::Bool(*bool)),
+                Primitive::Text(text)   => Ok(Val::Text(text.clone())),
+                Primitive::Proc(proc)   => proc.call(context, Vec::default()),
+                Primitive::List(..)     => Ok(Val::from(self.clone())),
+                Primitive::Table(table) => table.roll(context),
+                Primitive::Record(..)   => Ok(Val::from(self.clone())),
+                Primitive::Void         => Ok(Val::Void),
             } // match ..
         } // fn ..
+
+
+        /// Returns the item lying at a given index of a list.
+        pub(crate) fn item(items: &[Primitive], index: i16) -> Result<&Primitive, InterpreterErr> {
+            usize::try_from(index).ok()
+                .and_then(|i| items.get(i))
+                .ok_or(InterpreterErr::IndexOutOfBounds(index, items.len()))
+        } // fn ..
+
+
+        /// Returns the mutable item lying at a given index of a list.
+        pub(crate) fn item_mut(items: &mut [Primitive], index: i16) -> Result<&mut Primitive, InterpreterErr> {
+            let len = items.len();
+            usize::try_from(index).ok()
+                .and_then(|i| items.get_mut(i))
+                .ok_or(InterpreterErr::IndexOutOfBounds(index, len))
+        } // fn ..
+
+
+        /// Returns the field lying at the end of a path of nested records.
+        pub(crate) fn field(&self, path: &[Symbol]) -> Result<&Primitive, InterpreterErr> {
+            path.iter().try_fold(self, |primitive, name| match primitive {
+                Primitive::Record(record) => record.field(name),
+                _                         => Err(InterpreterErr::NotARecord),
+            }) // try_fold()
+        } // fn ..
+
+
+        /// Returns the mutable field lying at the end of a path of nested records.
+        pub(crate) fn field_mut(&mut self, path: &[Symbol]) -> Result<&mut Primitive, InterpreterErr> {
+            path.iter().try_fold(self, |primitive, name| match primitive {
+                Primitive::Record(record) => record.field_mut(name),
+                _                         => Err(InterpreterErr::NotARecord),
+            }) // try_fold()
+        } // fn ..
+    } // impl ..
+
+
+    impl From<i16> for Primitive {
+        fn from(value: i16) -> Self { Primitive::Int(value) }
+    } // impl ..
+
+    impl From<bool> for Primitive {
+        fn from(value: bool) -> Self { Primitive::Bool(value) }
+    } // impl ..
+
+    impl From<&str> for Primitive {
+        fn from(value: &str) -> Self { Primitive::Text(String::from(value)) }
+    } // impl ..
+
+    impl From<String> for Primitive {
+        fn from(value: String) -> Self { Primitive::Text(value) }
+    } // impl ..
+
+    impl From<Record> for Primitive {
+        fn from(value: Record) -> Self { Primitive::Record(value) }
+    } // impl ..
+
+    impl From<()> for Primitive {
+        fn from(_: ()) -> Self { Primitive::Void }
+    } // impl ..
+
+    impl<T: Into<Primitive>> From<Vec<T>> for Primitive {
+        fn from(value: Vec<T>) -> Self { Primitive::List(value.into_iter().map(Into::into).collect()) }
+    } // impl ..
+
+
+    impl TryFrom<Primitive> for i16 {
+        type Error = InterpreterErr;
+        fn try_from(value: Primitive) -> Result<Self, Self::Error> {
+            match value {
+                Primitive::Int(int) => Ok(int),
+                value               => Err(InterpreterErr::MismatchedValue(PrimitiveId::Int, value.id())),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+    impl TryFrom<Primitive> for bool {
+        type Error = InterpreterErr;
+        fn try_from(value: Primitive) -> Result<Self, Self::Error> {
+            match value {
+                Primitive::Bool(bool) => Ok(bool),
+                value                 => Err(InterpreterErr::MismatchedValue(PrimitiveId::Bool, value.id())),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+    impl TryFrom<Primitive> for String {
+        type Error = InterpreterErr;
+        fn try_from(value: Primitive) -> Result<Self, Self::Error> {
+            match value {
+                Primitive::Text(text) => Ok(text),
+                value                 => Err(InterpreterErr::MismatchedValue(PrimitiveId::Text, value.id())),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+    impl TryFrom<Primitive> for Record {
+        type Error = InterpreterErr;
+        fn try_from(value: Primitive) -> Result<Self, Self::Error> {
+            match value {
+                Primitive::Record(record) => Ok(record),
+                value                     => Err(InterpreterErr::MismatchedValue(PrimitiveId::Record, value.id())),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+    impl<T: TryFrom<Primitive, Error = InterpreterErr>> TryFrom<Primitive> for Vec<T> {
+        type Error = InterpreterErr;
+        fn try_from(value: Primitive) -> Result<Self, Self::Error> {
+            match value {
+                Primitive::List(items) => items.into_iter().map(T::try_from).collect(),
+                value                  => Err(InterpreterErr::MismatchedValue(PrimitiveId::List, value.id())),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl From<Primitive> for Val {
+        fn from(value: Primitive) -> Self {
+            match value {
+                Primitive::Int(int)     => Val::Int(int),
+                Primitive::Bool(bool)   => Val::Bool(bool),
+                Primitive::Text(text)   => Val::Text(text),
+                Primitive::Proc(proc)   => Val::Proc(Box::new(proc)),
+                Primitive::List(items)  => Val::List(items.into_iter().map(|item| Expr::Val(Val::from(item))).collect()),
+                Primitive::Table(table) => Val::Table(Box::new(table)),
+                Primitive::Record(rec)  => Val::Record(rec.fields.into_iter().map(|(name, field)| (name, Expr::Val(Val::from(field)))).collect()),
+                Primitive::Void         => Val::Void,
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Capture {
+        /// Creates a cell holding a given value.
+        pub(crate) fn new(value: Primitive) -> Self { Capture(Arc::new(Mutex::new(value))) }
+
+
+        /// Returns the value held by the cell.
+        pub(crate) fn get(&self) -> Primitive { self.0.lock().unwrap_or_else(PoisonError::into_inner).clone() }
+
+
+        /// Replaces the value held by the cell.
+        pub(crate) fn set(&self, value: Primitive) { *self.0.lock().unwrap_or_else(PoisonError::into_inner) = value }
+
+
+        /// Runs a function during which every cell is serialised, or deserialised, through the same table of ids.
+        pub(crate) fn shared<T>(f: impl FnOnce() -> T) -> T {
+            let _guard = CellsGuard(CELLS.with(|cells| {
+                let mut cells = cells.borrow_mut();
+                cells.is_none() && cells.replace(Cells::default()).is_none()
+            })); // let ..
+            f()
+        } // fn ..
+
+
+        /// Returns the address identifying the cell.
+        fn addr(&self) -> usize { Arc::as_ptr(&self.0) as usize }
+    } // impl ..
+
+
+    impl Drop for CellsGuard {
+        fn drop(&mut self) { if self.0 { CELLS.with(|cells| cells.borrow_mut().take()); } }
+    } // impl ..
+
+
+    impl PartialEq for Capture {
+        fn eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.0, &other.0) }
+    } // impl ..
+
+    impl Eq for Capture {}
+
+
+    impl Hash for Capture {
+        fn hash<H: Hasher>(&self, state: &mut H) { self.addr().hash(state) }
+    } // impl ..
+
+
+    impl fmt::Debug for Capture {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "Capture({:#x})", self.addr()) }
+    } // impl ..
+
+
+    impl Serialize for Capture {
+        /// Writes the id of the cell, along with its value the first time the cell is met.
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Capture::shared(|| {
+                let (id, first) = CELLS.with(|cells| {
+                    let mut cells = cells.borrow_mut();
+                    let cells     = cells.get_or_insert_with(Cells::default);
+                    let next      = cells.ids.len();
+                    let id        = *cells.ids.entry(self.addr()).or_insert(next);
+                    (id, id == next)
+                }); // let ..
+
+                (id, first.then(|| self.get())).serialize(serializer)
+            }) // shared()
+        } // fn ..
+    } // impl ..
+
+
+    impl<'de> Deserialize<'de> for Capture {
+        /// Reads a cell back, every id met again resolving to the same cell.
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Capture::shared(|| {
+                let (id, value) = <(usize, Option<Primitive>)>::deserialize(deserializer)?;
+                let capture     = CELLS.with(|cells| {
+                    cells.borrow_mut().get_or_insert_with(Cells::default)
+                        .cells.entry(id)
+                        .or_insert_with(|| Capture::new(Primitive::Void))
+                        .clone()
+                }); // let ..
+
+                if let Some(value) = value { capture.set(value); }
+                Ok(capture)
+            }) // shared()
+        } // fn ..
+    } // impl ..
+
+
+    impl Proc {
+        /// Creates a procedure capturing the current value of every initialised register its body refers to.
+        pub(crate) fn capture(
+            context: &SyntaxContext,
+            params:  &[Symbol],
+            body:    &Decl,
+        ) -> Self {
+            Proc {
+                params:   params.to_vec(),
+                captures: body.refs().into_iter()
+                    .filter(|name| !params.contains(name))
+                    .filter_map(|name| context.registers().get(&name).map(|value| (name, Capture::new(value.clone()))))
+                    .collect(),
+                body:     Arc::new(body.clone()),
+            } // Proc ..
+        } // fn ..
+
+
+        /// Calls the procedure, binding its captures and arguments for the duration of the call and keeping what it wrote to its captures.
+        pub(crate) fn call(
+            &self,
+            context: &mut SyntaxContext,
+            args:    Vec<Primitive>,
+        ) -> Result<Val, InterpreterErr> {
+
+            if args.len() != self.params.len() { return Err(InterpreterErr::WrongArity(self.params.len(), args.len())) }
+
+            let bindings = self.captures.iter()
+                .map(|(name, capture)| (*name, capture.get()))
+                .chain(self.params.iter().cloned().zip(args))
+                .collect::<Vec<(Symbol, Primitive)>>();
+            let saved    = bindings.iter()
+                .map(|(name, _)| (*name, context.registers().get(name).cloned()))
+                .collect::<Vec<(Symbol, Option<Primitive>)>>();
+
+
+            context.enter_call()?;
+            let output = bindings.into_iter()
+                .try_for_each(|(name, value)| match value { Primitive::Void => { context.del_reg(&name); Ok(()) }, _ => context.new_reg(&name, value) })
+                .and_then(|_| Stm::scope(context, &self.body))
+                .map(|output| output.unwrap_or(Val::Void));
+
+            for (name, capture) in &self.captures { capture.set(context.registers().get(name).cloned().unwrap_or(Primitive::Void)) }
+            saved.into_iter().rev().for_each(|(name, value)| context.restore_reg(&name, value));
+            context.exit_call();
+
+            output
+        } // fn ..
+    } // impl ..
+
+
+    impl Table {
+        /// Creates a table, checking that its rows neither overlap nor leave gaps within the range of its dice.
+        pub(crate) fn new(
+            index: usize,
+            dice:  Expr,
+            rows:  Vec<(i16, i16, Decl)>,
+        ) -> Result<Self, InterpreterErr> {
+
+            let mut rows = rows;
+            rows.sort_by_key(|(from, to, _)| (*from, *to));
+
+            for pair in rows.windows(2usize) {
+                let (to, from) = (pair[0usize].1, pair[1usize].0);
+                if from <= to                     { return Err(InterpreterErr::OverlappingRows(index, from)) }
+                if to.saturating_add(1i16) < from { return Err(InterpreterErr::MissingRows(index, to + 1i16)) }
+            } // for ..
+
+            if let Some((min, max)) = Self::bounds(&dice) {
+                match (rows.first(), rows.last()) {
+                    (Some((from, ..)), _) if *from > min => return Err(InterpreterErr::MissingRows(index, min)),
+                    (_, Some((_, to, _))) if *to < max   => return Err(InterpreterErr::MissingRows(index, to + 1i16)),
+                    (None, _)                            => return Err(InterpreterErr::MissingRows(index, min)),
+                    _                                    => (),
+                } // match ..
+            } // if ..
+
+            Ok(Table { dice, rows })
+        } // fn ..
+
+
+        /// Returns the lowest and highest rolls of a plain dice throw such as `1d100`.
+        fn bounds(dice: &Expr) -> Option<(i16, i16)> {
+            match dice {
+                Expr::Op(Op::Dice(Val::Int(face), n, None)) => match **n {
+                    Expr::Val(Val::Int(n)) if n > 0i16 && *face > 0i16 => Some((n, n.checked_mul(*face)?)),
+                    _                                                  => None,
+                }, // => ..
+                Expr::Val(Val::Expr(expr))                  => Table::bounds(expr),
+                _                                           => None,
+            } // match ..
+        } // fn ..
+
+
+        /// Rolls the dice of the table and outputs the matching result.
+        pub(crate) fn roll(&self, context: &mut SyntaxContext) -> Result<Val, InterpreterErr> {
+            let roll = self.dice.as_int(context)?;
+            self.lookup(context, roll)
+        } // fn ..
+
+
+        /// Outputs the result matching a given roll.
+        pub(crate) fn lookup(&self, context: &mut SyntaxContext, roll: i16) -> Result<Val, InterpreterErr> {
+
+            let decl = self.rows.iter()
+                .find(|(from, to, _)| (*from..=*to).contains(&roll))
+                .map(|(_, _, decl)| decl)
+                .ok_or(InterpreterErr::NoTableRow(roll))?;
+
+            context.enter_call()?;
+            let output = Stm::scope(context, decl).map(|output| output.unwrap_or(Val::Void));
+            context.exit_call();
+
+            output
+        } // fn ..
+    } // impl ..
+
+
+    impl Record {
+        /// Returns every field of the record, in the order they were declared.
+        pub fn fields(&self) -> &[(Symbol, Primitive)] { &self.fields }
+
+
+        /// Returns the value of the field with a given name.
+        pub fn get(&self, name: &str) -> Option<&Primitive> { self.field(&Symbol::lookup(name)?).ok() }
+
+
+        /// Returns the field with a given name.
+        pub(crate) fn field(&self, name: &Symbol) -> Result<&Primitive, InterpreterErr> {
+            self.fields.iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value)
+                .ok_or(InterpreterErr::UnknownField(*name))
+        } // fn ..
+
+
+        /// Returns the mutable field with a given name.
+        pub(crate) fn field_mut(&mut self, name: &Symbol) -> Result<&mut Primitive, InterpreterErr> {
+            self.fields.iter_mut()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value)
+                .ok_or(InterpreterErr::UnknownField(*name))
+        } // fn ..
+    } // impl ..
+
+
+    impl<S: Into<Symbol>, T: Into<Primitive>> FromIterator<(S, T)> for Record {
+        /// Creates a record from its fields, a field declared twice keeping its last value.
+        fn from_iter<I: IntoIterator<Item = (S, T)>>(iter: I) -> Self {
+            let mut record = Record { fields: Vec::default() };
+            for (name, value) in iter {
+                let (name, value) = (name.into(), value.into());
+                match record.field_mut(&name) {
+                    Ok(field) => *field = value,
+                    Err(_)    => record.fields.push((name, value)),
+                } // match ..
+            } // for ..
+            record
+        } // fn ..
+    } // impl ..
+
+
+    impl fmt::Debug for Record {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{{")?;
+            for (i, (name, value)) in self.fields.iter().enumerate() {
+                write!(f, "{} {}: {:?}", if i == 0usize { "" } else { "," }, name.as_ref(), value)?;
+            } // for ..
+            write!(f, "{}}}", if self.fields.is_empty() { "" } else { " " })
+        } // fn ..
+    } // impl ..
+
+
+    impl fmt::Display for PrimitiveId {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", match self {
+                PrimitiveId::Int    => "integer",
+                PrimitiveId::Bool   => "boolean",
+                PrimitiveId::Text   => "text",
+                PrimitiveId::Proc   => "proc",
+                PrimitiveId::List   => "list",
+                PrimitiveId::Table  => "table",
+                PrimitiveId::Record => "record",
+                PrimitiveId::Void   => "void",
+            }) // write()
+        } // fn ..
     } // impl ..
@@ -0,0 +1,52 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::sync::Arc;
+
+    use crate::{
+        InterpreterErr,
+        Primitive, PrimitiveId,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// A Rust function scripts call like a procedure, checked against the types it was registered with.
+    #[derive(Clone)]
+    pub(crate) struct Host {
+        pub(crate) params: Vec<PrimitiveId>,
+        pub(crate) output: PrimitiveId,
+                   func:   Arc<dyn Fn(Vec<Primitive>) -> Result<Primitive, InterpreterErr> + Send + Sync>,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl Host {
+        /// Wraps a Rust function taking arguments of the given types and outputting a value of another one.
+        pub(crate) fn new(
+            params: &[PrimitiveId],
+            output: PrimitiveId,
+            func:   impl Fn(Vec<Primitive>) -> Result<Primitive, InterpreterErr> + Send + Sync + 'static,
+        ) -> Self { Host { params: params.to_vec(), output, func: Arc::new(func) } }
+
+
+        /// Calls the function, failing if the arguments or the output don't have the expected types.
+        pub(crate) fn call(&self, args: Vec<Primitive>) -> Result<Primitive, InterpreterErr> {
+
+            if args.len() != self.params.len() { return Err(InterpreterErr::WrongArity(self.params.len(), args.len())) }
+            if let Some((param, arg)) = self.params.iter().zip(args.iter()).find(|(param, arg)| **param != arg.id()) {
+                return Err(InterpreterErr::MismatchedValue(param.clone(), arg.id()))
+            } // if ..
+
+            match (self.func)(args)? {
+                output if output.id() == self.output => Ok(output),
+                output                               => Err(InterpreterErr::MismatchedValue(self.output.clone(), output.id())),
+            } // match ..
+        } // fn ..
+    } // impl ..
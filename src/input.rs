@@ -0,0 +1,102 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::collections::VecDeque;
+    use std::io::{stdin, stdout, Write};
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use crate::{
+        Primitive, PrimitiveId,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// Where the answers asked for by the scripts of a context come from.
+    pub trait Input: Send + Sync {
+        /// Asks a question, returning the answer or nothing once no answer can be given anymore.
+        fn ask(&self, prompt: &str) -> Option<String>;
+
+        /// Tells why an answer was rejected, before the question gets asked again.
+        fn reject(&self, _answer: &str, _reason: &str) {}
+    } // trait ..
+
+
+    /// An input asking its questions on the standard output and reading the answers from the standard input.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct StdInput;
+
+
+    /// An input giving answers written in advance, in order, its clones sharing the same answers.
+    #[derive(Debug, Default, Clone)]
+    pub struct ScriptedInput {
+        answers:  Arc<Mutex<VecDeque<String>>>,
+        prompts:  Arc<Mutex<Vec<String>>>,
+        rejected: Arc<Mutex<Vec<String>>>,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl Input for StdInput {
+        fn ask(&self, prompt: &str) -> Option<String> {
+            print!("{} ", prompt);
+            stdout().flush().ok()?;
+
+            let mut answer = String::new();
+            match stdin().read_line(&mut answer) {
+                Ok(0usize) | Err(_) => None,
+                Ok(_)               => Some(String::from(answer.trim_end_matches(['\n', '\r']))),
+            } // match ..
+        } // fn ..
+
+
+        fn reject(&self, _: &str, reason: &str) { eprintln!("{}!", reason); }
+    } // impl ..
+
+
+    impl Input for ScriptedInput {
+        fn ask(&self, prompt: &str) -> Option<String> {
+            self.prompts.lock().unwrap_or_else(PoisonError::into_inner).push(String::from(prompt));
+            self.answers.lock().unwrap_or_else(PoisonError::into_inner).pop_front()
+        } // fn ..
+
+
+        fn reject(&self, answer: &str, _: &str) { self.rejected.lock().unwrap_or_else(PoisonError::into_inner).push(String::from(answer)); }
+    } // impl ..
+
+
+    impl ScriptedInput {
+        /// Creates an input giving the given answers, in order.
+        pub fn new<S: Into<String>>(answers: impl IntoIterator<Item = S>) -> Self {
+            ScriptedInput { answers: Arc::new(Mutex::new(answers.into_iter().map(Into::into).collect())), ..Self::default() }
+        } // fn ..
+
+        /// Returns every question asked so far, in order.
+        pub fn prompts(&self) -> Vec<String> { self.prompts.lock().unwrap_or_else(PoisonError::into_inner).clone() }
+
+        /// Returns every answer rejected so far, in order.
+        pub fn rejected(&self) -> Vec<String> { self.rejected.lock().unwrap_or_else(PoisonError::into_inner).clone() }
+    } // impl ..
+
+
+    /// Reads an answer as a value of a given type, eventually lying in a given range, or tells why it can't be.
+    pub(crate) fn read_answer(id: &PrimitiveId, answer: &str, range: Option<(i16, i16)>) -> Result<Primitive, String> {
+        match (id, answer.trim().to_lowercase().as_str()) {
+            (PrimitiveId::Int, answer) => match (answer.parse::<i16>(), range) {
+                (Ok(int), Some((from, to))) if int < from || int > to => Err(format!("Expected an integer from `{}` to `{}`", from, to)),
+                (Ok(int), _)                                          => Ok(Primitive::Int(int)),
+                (Err(_), _)                                           => Err(String::from("Expected an integer")),
+            }, // => ..
+            (PrimitiveId::Bool, "y" | "yes" | "true")  => Ok(Primitive::Bool(true)),
+            (PrimitiveId::Bool, "n" | "no"  | "false") => Ok(Primitive::Bool(false)),
+            (PrimitiveId::Bool, _)                     => Err(String::from("Expected yes or no")),
+            (PrimitiveId::Text, _)                     => Ok(Primitive::Text(String::from(answer))),
+            (id, _)                                    => Err(format!("Can't ask for a `{}` value", id)),
+        } // match ..
+    } // fn ..
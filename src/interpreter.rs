@@ -0,0 +1,228 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::error::Error;
+    use std::fmt;
+    use std::fs::read_to_string;
+    use std::io;
+    use std::path::Path;
+    use rusty_toolkit::LexingErr;
+
+    use crate::{
+        WsToken,
+        Stm,
+        SyntaxContext, SyntaxElement,
+        InterpreterErr,
+        Primitive,
+        Symbol,
+        TypeChecker, Program,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// Runs scripts and procedures within a context kept from one run to the next, on the same virtual machine as the command line.
+    #[derive(Default)]
+    pub struct Interpreter {
+        context: SyntaxContext,
+    } // struct ..
+
+
+    /// An error raised while evaluating a script.
+    #[derive(Debug)]
+    pub enum EvalErr {
+        Io          (io::Error),
+        Lexing      (LexingErr),
+        Interpreting(Vec<InterpreterErr>),
+    } // enum ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl Interpreter {
+        /// Creates an interpreter with an empty context.
+        pub fn new() -> Self { Self::default() }
+
+        /// Returns the context the scripts run within.
+        pub fn context(&self) -> &SyntaxContext { &self.context }
+
+        /// Returns the mutable context the scripts run within, to read or seed its registers.
+        pub fn context_mut(&mut self) -> &mut SyntaxContext { &mut self.context }
+
+
+        /// Checks and runs a script, returning the value of its top-level `out` statement or `Void` when it has none.
+        pub fn eval(&mut self, src: &str) -> Result<Primitive, EvalErr> {
+
+            self.context.push(src)?;
+            let stms = match Stm::scan(&mut self.context, Vec::default(), WsToken::Eof) {
+                Ok(stms) => stms,
+                Err(err) => { self.context.discard(); return Err(EvalErr::from(err)) },
+            }; // let ..
+            TypeChecker::new(&self.context).check(&stms)?;
+
+            Ok(Program::compile(&self.context, &stms).run(&mut self.context)?)
+        } // fn ..
+
+
+        /// Checks and runs a script file, as `eval` does.
+        pub fn eval_file(&mut self, path: impl AsRef<Path>) -> Result<Primitive, EvalErr> {
+            let src = read_to_string(path)?;
+            self.eval(&src)
+        } // fn ..
+
+
+        /// Calls the procedure stored in a given register with some arguments, returning its output.
+        pub fn call(&mut self, name: &str, args: Vec<Primitive>) -> Result<Primitive, EvalErr> {
+            Ok(Program::compile(&self.context, &[]).call(&mut self.context, &Symbol::from(name), args)?)
+        } // fn ..
+    } // impl ..
+
+
+    impl From<SyntaxContext> for Interpreter {
+        fn from(context: SyntaxContext) -> Self { Interpreter { context } }
+    } // impl ..
+
+
+    impl From<io::Error> for EvalErr {
+        fn from(err: io::Error) -> Self { EvalErr::Io(err) }
+    } // impl ..
+
+    impl From<LexingErr> for EvalErr {
+        fn from(err: LexingErr) -> Self { EvalErr::Lexing(err) }
+    } // impl ..
+
+    impl From<InterpreterErr> for EvalErr {
+        fn from(err: InterpreterErr) -> Self { EvalErr::Interpreting(vec![err]) }
+    } // impl ..
+
+    impl From<Vec<InterpreterErr>> for EvalErr {
+        fn from(errs: Vec<InterpreterErr>) -> Self { EvalErr::Interpreting(errs) }
+    } // impl ..
+
+
+    impl Error for EvalErr {}
+    impl fmt::Display for EvalErr {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                EvalErr::Io(err)            => write!(f, "{}", err),
+                EvalErr::Lexing(err)        => write!(f, "{}", err),
+                EvalErr::Interpreting(errs) => {
+                    for (i, err) in errs.iter().enumerate() { write!(f, "{}{}", if i == 0usize { "" } else { "\n" }, err)?; }
+                    Ok(())
+                }, // => ..
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{Interpreter, EvalErr, InterpreterErr, Limits, Primitive, PrimitiveId, Collector, Span};
+
+        #[test]
+        fn eval_returns_the_top_level_output() {
+            let mut whist = Interpreter::new();
+            assert_eq!(whist.eval("out -> 1 + 2;").unwrap(), Primitive::Int(3i16));
+            assert_eq!(whist.eval("if | 1 = 1 |: out -> 'x'; >>>> out -> 'y';").unwrap(), Primitive::from("x"));
+            assert_eq!(whist.eval("let [x] <- 1;").unwrap(), Primitive::Void);
+        } // fn ..
+
+        #[test]
+        fn calls_share_the_engine_of_scripts() {
+            let mut whist = Interpreter::new();
+            whist.context_mut().new_host("bonus", &[], PrimitiveId::Int, |_| Ok(Primitive::from(2i16)));
+            whist.eval("define [heal]([hp]): out -> integer [hp] + integer [bonus](); >>>> define [counter]: let [n] <- 0; out -> proc: set [n] <- integer [n] + 1; out -> integer [n]; >>>>; >>>> let [c] <- proc [counter]();").unwrap();
+
+            assert_eq!(whist.call("heal", vec![Primitive::from(10i16)]).unwrap(), Primitive::Int(12i16));
+            assert_eq!(whist.call("c", Vec::default()).unwrap(), Primitive::Int(1i16));
+            assert_eq!(whist.eval("out -> integer [c];").unwrap(), Primitive::Int(2i16));
+            assert_eq!(whist.call("c", Vec::default()).unwrap(), Primitive::Int(3i16));
+            assert!(matches!(whist.call("missing", Vec::default()), Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::UninitReg(..)])));
+
+            whist.eval("define [spin]: let [i] <- 0; while | 1 = 1 |: set [i] <- integer [i] + 1; >>>> >>>>").unwrap();
+            whist.context_mut().set_limits(Limits { statements: 100usize, ..Limits::default() });
+            assert!(matches!(whist.call("spin", Vec::default()), Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::StatementsExceeded(100usize)])));
+            assert!(matches!(whist.eval("let [x] <- integer [spin];"), Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::StatementsExceeded(100usize)])));
+        } // fn ..
+
+        #[test]
+        fn host_functions_are_checked_and_called() {
+            let mut whist = Interpreter::new();
+            whist.context_mut().new_host("add",  &[PrimitiveId::Int, PrimitiveId::Int], PrimitiveId::Int, |args| Ok(Primitive::from(args.iter().map(|arg| i16::try_from(arg.clone())).sum::<Result<i16, _>>()?)));
+            whist.context_mut().new_host("fail", &[],                                   PrimitiveId::Int, |_| Err(InterpreterErr::DivisionByZero));
+            whist.context_mut().new_host("liar", &[],                                   PrimitiveId::Int, |_| Ok(Primitive::from("x")));
+
+            assert_eq!(whist.eval("out -> integer [add](2, integer [add](1, 3));").unwrap(), Primitive::Int(6i16));
+            assert!(matches!(whist.eval("out -> integer [add]('a', 3);"), Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::MismatchedType(_, PrimitiveId::Int, PrimitiveId::Text)])));
+            assert!(matches!(whist.eval("out -> integer [add](1);"),      Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::WrongArity(2usize, 1usize)])));
+            assert!(matches!(whist.eval("out -> text [add](1, 2);"),      Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::MismatchedType(_, PrimitiveId::Text, PrimitiveId::Int)])));
+            assert!(matches!(whist.eval("out -> integer [fail]();"),      Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::DivisionByZero])));
+            assert!(matches!(whist.eval("out -> integer [liar]();"),      Err(EvalErr::Interpreting(errs)) if matches!(errs.as_slice(), [InterpreterErr::MismatchedValue(PrimitiveId::Int, PrimitiveId::Text)])));
+
+            whist.context_mut().new_host("add", &[PrimitiveId::Int], PrimitiveId::Int, |args| Ok(args[0].clone()));
+            assert_eq!(whist.eval("out -> integer [add](4);").unwrap(), Primitive::Int(4i16));
+            assert_eq!(whist.eval("let [add] <- 7; out -> integer [add];").unwrap(), Primitive::Int(7i16));
+        } // fn ..
+
+        #[test]
+        fn told_values_carry_their_source_span() {
+            let src   = "let [x] <- 2;\nif | 1 = 1 |:\n    tell -> integer [x] + 1;\n>>>>";
+            let told  = Collector::default();
+            let mut whist = Interpreter::new();
+            whist.context_mut().set_output(told.clone());
+            whist.eval(src).unwrap();
+
+            let events = told.take();
+            assert_eq!(events.len(), 1usize);
+            assert_eq!(events[0].value, Primitive::Int(3i16));
+            assert_eq!(events[0].span, Span { start: 32usize, end: 56usize, line: 3usize, column: 5usize });
+            assert_eq!(&src[events[0].span.start..events[0].span.end], "tell -> integer [x] + 1;");
+        } // fn ..
+
+        #[test]
+        fn saved_sessions_restore_procedures_and_dice() {
+            let path = std::env::temp_dir().join(format!("whist-session-{}.json", std::process::id()));
+            let mut whist = Interpreter::new();
+            whist.eval("define [heal]([n]): out -> integer [n] + 2; >>>> define [counter]: let [n] <- 0; out -> proc: set [n] <- integer [n] + 1; out -> integer [n]; >>>>; >>>> let [c] <- proc [counter](); let [x] <- 3d6;").unwrap();
+            whist.call("c", Vec::default()).unwrap();
+            whist.context().save(&path).unwrap();
+
+            let mut resumed = Interpreter::new();
+            resumed.context_mut().restore(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(resumed.call("heal", vec![Primitive::from(5i16)]).unwrap(), Primitive::Int(7i16));
+            assert_eq!(resumed.call("c", Vec::default()).unwrap(), Primitive::Int(2i16));
+            assert_eq!(resumed.eval("out -> integer [x];").unwrap(), whist.eval("out -> integer [x];").unwrap());
+            for _ in 0usize..8usize {
+                assert_eq!(resumed.eval("out -> 10d20;").unwrap(), whist.eval("out -> 10d20;").unwrap());
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn written_state_keeps_closures_shared_and_long_bodies() {
+            let mut whist = Interpreter::new();
+            whist.eval("define [counter]: let [n] <- 0; out -> proc: set [n] <- integer [n] + 1; out -> integer [n]; >>>>; >>>> let [inc] <- proc [counter](); let [same] <- proc [inc];").unwrap();
+            whist.eval(&format!("define [long]: out -> {}1; >>>>", "1 + ".repeat(1_000usize))).unwrap();
+            whist.call("inc", Vec::default()).unwrap();
+
+            let mut state = Vec::default();
+            whist.context().write_state(&mut state).unwrap();
+            let mut resumed = Interpreter::new();
+            resumed.context_mut().read_state(state.as_slice()).unwrap();
+
+            assert_eq!(resumed.call("inc",  Vec::default()).unwrap(), Primitive::Int(2i16));
+            assert_eq!(resumed.call("same", Vec::default()).unwrap(), Primitive::Int(3i16));
+            assert_eq!(resumed.call("inc",  Vec::default()).unwrap(), Primitive::Int(4i16));
+            assert_eq!(resumed.call("long", Vec::default()).unwrap(), Primitive::Int(1_001i16));
+        } // fn ..
+    } // mod ..
@@ -2,15 +2,37 @@
 // M O D U L E S
 //###############
     
+    mod symbol;
+    mod rng;
     mod generic;
+    mod host;
     mod syntax;
     mod tokens;
     mod stm;
     mod expr;
+    mod notation;
+    mod check;
+    mod vm;
+    mod runner;
+    mod interpreter;
+    mod output;
+    mod input;
 
-    pub(crate) use generic::{Primitive, PrimitiveId};
-    pub        use syntax::{SyntaxContext, SyntaxElement};
-    pub(crate) use syntax::InterpreterErr;
-    pub(crate) use expr::{Expr, Val};
-    pub        use stm::{Stm, Decl};
-    pub        use tokens::WsToken;
+    pub        use generic::{Primitive, PrimitiveId, Proc, Table, Record};
+    pub(crate) use generic::Capture;
+    pub(crate) use host::Host;
+    pub        use syntax::{SyntaxContext, SyntaxElement, Limits};
+    pub        use syntax::InterpreterErr;
+    pub        use expr::{Expr, Val, Op};
+    pub(crate) use expr::{CmpTo, Pool, Keep, Faces, Macro};
+    pub        use stm::{Stm, Decl, Stats};
+    pub(crate) use stm::{Bounds, Bound, Sample};
+    pub        use tokens::{WsToken, Span};
+    pub        use symbol::Symbol;
+    pub(crate) use rng::Rng;
+    pub        use check::TypeChecker;
+    pub        use vm::Program;
+    pub        use runner::TestRunner;
+    pub        use interpreter::{Interpreter, EvalErr};
+    pub        use output::{Output, TellEvent, StdOutput, Collector};
+    pub        use input::{Input, StdInput, ScriptedInput};
@@ -4,19 +4,59 @@
 
     use whist_lang::{
         WsToken,
-        Stm, Decl,
+        Stm,
         SyntaxContext, SyntaxElement,
+        TypeChecker, Program,
+        TestRunner,
+        Expr,
+        Symbol,
+    }; // use ..
+    use rusty_toolkit::Lexer;
+    use rustyline::{
+        Editor, Config, CompletionType, Context, Helper,
+        completion::Completer,
+        hint::Hinter,
+        highlight::Highlighter,
+        validate::Validator,
+        history::DefaultHistory,
+        error::ReadlineError,
     }; // use ..
 
     use std::env;
     use std::fs::read_to_string;
-    use std::io::stdin;
+    use std::path::PathBuf;
+    use std::process;
 
 
 //#######################
 // D E F I N I T I O N S
 //#######################
 
+    const PROMPT:       &str = "whist> ";
+    const CONTINUATION: &str = "  ...> ";
+    const HISTORY:      &str = ".whist_history";
+    const COMMANDS:     &[&str] = &["help", "regs", "reset", "load", "save", "restore", "quit"];
+    const HELP:         &str = "\
+Enter statements to run them, a statement spanning several lines until its blocks close.
+
+  :help            Shows this help.
+  :regs            Lists every register along with its value.
+  :reset           Forgets every register, procedure and macro.
+  :load <file>     Runs a script file.
+  :save <file>     Saves every register, procedure, macro and the dice to a file.
+  :restore <file>  Resumes a session saved to a file.
+  :quit            Ends the session.
+
+Entering a command abandons any unfinished statement, and so does Ctrl-C.
+Tab completes keywords, commands and register names.";
+
+
+    /// Completes the input of the REPL, knowing the registers allocated so far.
+    struct Completion {
+        registers: Vec<Symbol>,
+    } // struct ..
+
+
     fn main() {
 
         let mut context = SyntaxContext::default();
@@ -30,47 +70,232 @@
                         match match arg.as_str() {
                             "line" => { context.push(args.get(3usize).unwrap_or(&String::from(""))) },
                             "file" => { context.push(&read_to_string(args.get(3usize).unwrap_or(&String::from(""))).unwrap_or_default()) }
-                            _      => ( Ok(()) ),
+                            _      => Ok(()),
                         } {
-                            Ok(_)    => (),
+                            Ok(_)    => { execute(&mut context); },
                             Err(err) => eprintln!("ERROR: {}", err),
                         } // match ..
                     } // if ..
                 }, // => ..
+                "test" => process::exit(test(
+                    args.get(2usize).map(String::as_str).unwrap_or_default(),
+                    args.get(3usize).and_then(|seed| seed.parse().ok()).unwrap_or_default(),
+                )), // => ..
+                "roll" => process::exit(roll(&mut context, &args[2usize..])),
                 _=> (),
             } // match ..
         } // if ..
 
-        
-        match match Stm::scan(&mut context, Vec::default(), WsToken::Eof) {
-            Ok(stms) => { Stm::run(&mut context, &Decl::Closure(stms)) },
-            Err(err) => { Err(err) },
-        } {
-            Ok(_)    => (),
-            Err(err) => eprintln!("ERROR: {}", err),
-        } // match ..
+
+        repl(&mut context);
+    } // fn ..
 
 
-        let mut buffer  = String::new();
-        while buffer   != "quit" {
+    /// Reads statements from the standard input until it ends or `:quit` is entered, prompting for more lines until a block closes.
+    fn repl(context: &mut SyntaxContext) {
 
-            stdin().read_line(&mut buffer).expect("ERROR: Failed to read line!");
+        let config     = Config::builder().auto_add_history(true).completion_type(CompletionType::List).build();
+        let mut editor = match Editor::<Completion, DefaultHistory>::with_config(config) {
+            Ok(editor) => editor,
+            Err(err)   => { eprintln!("ERROR: {}", err); return },
+        }; // let ..
 
-            match context.push(&buffer) {
-                Ok(_)    => (),
-                Err(err) => { eprintln!("ERROR: {}", err); break; },
-            } // match ..
+        let history = history_path();
+        if let Some(path) = &history { editor.load_history(path).unwrap_or_default(); }
+
+        let mut pending = String::new();
+        loop {
+
+            editor.set_helper(Some(Completion { registers: context.register_names() }));
+            let line = match editor.readline(if pending.is_empty() { PROMPT } else { CONTINUATION }) {
+                Ok(line)                        => line,
+                Err(ReadlineError::Interrupted) => { pending.clear(); continue },
+                Err(ReadlineError::Eof)         => break,
+                Err(err)                        => { eprintln!("ERROR: {}", err); break },
+            }; // let ..
+
+            if let Some(command) = line.trim().strip_prefix(':') {
+                pending.clear();
+                if !run_command(context, command) { break }
+                continue;
+            } // if ..
 
-            match match Stm::scan(&mut context, Vec::default(), WsToken::Eof) {
-                Ok(stms) => { Stm::run(&mut context, &Decl::Closure(stms)) },
-                Err(err) => { Err(err) },
-            } {
-                Ok(_)    => (),
-                Err(err) => { eprintln!("ERROR: {}", err); break; }
+            pending.push_str(&line);
+            pending.push('\n');
+            if !is_complete(&pending) { continue }
+
+            match context.push(&pending) {
+                Ok(())   => { execute(context); },
+                Err(err) => eprintln!("ERROR: {}", err),
             } // match ..
+            pending.clear();
+
+        } // loop ..
+
+        if let Some(path) = &history { editor.save_history(path).unwrap_or_else(|err| eprintln!("ERROR: {}", err)); }
+    } // fn ..
+
+
+    /// Returns the path of the history file, kept in the home directory.
+    fn history_path() -> Option<PathBuf> {
+        env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(|home| PathBuf::from(home).join(HISTORY))
+    } // fn ..
+
+
+    /// Runs a REPL command, returning whether the session goes on.
+    fn run_command(context: &mut SyntaxContext, command: &str) -> bool {
+        match command.split_once(char::is_whitespace).map(|(name, arg)| (name, arg.trim())).unwrap_or((command, "")) {
+            ("quit" | "q", _)  => return false,
+            ("help" | "h", _)  => println!("{}", HELP),
+            ("regs", _)        => context.describe_registers().iter().for_each(|(name, value)| println!("[{}] {}", name, value)),
+            ("reset", _)       => *context = SyntaxContext::default(),
+            ("load", "")       => eprintln!("ERROR: Expected a file to load!"),
+            ("load", path)     => match read_to_string(path) {
+                Ok(src)  => match context.push(&src) {
+                    Ok(())   => { execute(context); },
+                    Err(err) => eprintln!("ERROR: {}", err),
+                }, // => ..
+                Err(err) => eprintln!("ERROR: {}", err),
+            }, // => ..
+            ("save", "")       => eprintln!("ERROR: Expected a file to save to!"),
+            ("save", path)     => if let Err(err) = context.save(path) { eprintln!("ERROR: {}", err); },
+            ("restore", "")    => eprintln!("ERROR: Expected a file to restore from!"),
+            ("restore", path)  => if let Err(err) = context.restore(path) { eprintln!("ERROR: {}", err); },
+            (name, _)          => eprintln!("ERROR: Unknown command `:{}`, see `:help`!", name),
+        } // match ..
+        true
+    } // fn ..
+
+
+    /// Returns whether an input closes every block and parenthesis it opens and ends with a statement, input that fails to lex being complete so that its error gets reported.
+    fn is_complete(src: &str) -> bool {
 
+        let tokens = match Lexer::run::<WsToken>(src) {
+            Ok(tokens) => tokens,
+            Err(_)     => return true,
+        }; // let ..
 
-            buffer = String::new();
+        let depth = tokens.windows(2usize).fold(0isize, |depth, pair| match pair {
+            [WsToken::Colon, WsToken::Eq | WsToken::Ne | WsToken::Gt | WsToken::Ge | WsToken::Lt | WsToken::Le] => depth,
+            [WsToken::Colon | WsToken::LeftParen, _]                                                          => depth + 1isize,
+            [WsToken::Eos   | WsToken::RightParen, _]                                                         => depth - 1isize,
+            _                                                                                                 => depth,
+        }); // let ..
 
-        } // while ..
+        depth <= 0isize && matches!(tokens.iter().rev().nth(1usize), None | Some(WsToken::SemiColon | WsToken::Eos))
     } // fn ..
+
+
+    /// Scans, checks and runs the pending tokens of the context, returning whether it succeeded, any unscanned token being dropped on failure.
+    fn execute(context: &mut SyntaxContext) -> bool {
+        let errs = match Stm::scan(context, Vec::default(), WsToken::Eof) {
+            Ok(stms) => match TypeChecker::new(context).check(&stms) {
+                Ok(()) => match Program::compile(context, &stms).run(context) {
+                    Ok(_)    => return true,
+                    Err(err) => vec![err],
+                }, // => ..
+                Err(errs) => errs,
+            }, // => ..
+            Err(err) => vec![err],
+        }; // let ..
+
+        errs.iter().for_each(|err| eprintln!("ERROR: {}", err));
+        context.discard();
+        false
+    } // fn ..
+
+
+    impl Completer for Completion {
+        type Candidate = String;
+
+        /// Completes the word under the cursor with a register name after `[`, a command after a leading `:` and a keyword otherwise.
+        fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+
+            let start = line[..pos].char_indices().rev()
+                .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+                .map_or(0usize, |(i, c)| i + c.len_utf8());
+            let word  = &line[start..pos];
+
+            let candidates = match line[..start].trim_start() {
+                ":"                             => COMMANDS.to_vec(),
+                before if before.ends_with('[') => self.registers.iter().map(Symbol::as_str).collect(),
+                _                               => WsToken::KEYWORDS.iter().map(|(word, _)| *word).collect(),
+            }; // let ..
+
+            Ok((start, candidates.into_iter().filter(|candidate| candidate.starts_with(word)).map(String::from).collect()))
+        } // fn ..
+    } // impl ..
+
+    impl Hinter      for Completion { type Hint = String; }
+    impl Highlighter for Completion {}
+    impl Validator   for Completion {}
+    impl Helper      for Completion {}
+
+
+    /// Runs every test of a script file, returning the exit code of the run.
+    fn test(path: &str, seed: u64) -> i32 {
+
+        let runner = match read_to_string(path) {
+            Ok(src)  => match TestRunner::new(&src, seed) {
+                Ok(runner) => runner,
+                Err(err)   => { eprintln!("ERROR: {}", err); return 1i32 },
+            }, // => ..
+            Err(err) => { eprintln!("ERROR: {}", err); return 1i32 },
+        }; // let ..
+
+        let tests = match runner.discover() {
+            Ok(tests) => tests,
+            Err(errs) => { errs.iter().for_each(|err| eprintln!("ERROR: {}", err)); return 1i32 },
+        }; // let ..
+
+        let failed = tests.iter().filter(|name| match runner.run(name) {
+            Ok(())   => { println!("PASS {}", name); false },
+            Err(err) => { println!("FAIL {}: {}", name, err); true },
+        }).count();
+
+        println!("{} passed, {} failed (seed {})", tests.len() - failed, failed, seed);
+        if failed == 0usize { 0i32 } else { 1i32 }
+    } // fn ..
+
+
+    /// Rolls every given roll written in standard dice notation, returning the exit code of the run.
+    fn roll(context: &mut SyntaxContext, notations: &[String]) -> i32 {
+        notations.iter().fold(0i32, |code, notation| match Expr::from_notation(notation).and_then(|expr| expr.roll(context)) {
+            Ok(roll) => { println!("{}", roll); code },
+            Err(err) => { eprintln!("ERROR: {}", err); 1i32 },
+        }) // fold()
+    } // fn ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use super::is_complete;
+
+        #[test]
+        fn pending_input_is_complete_once_every_block_is_closed() {
+            for src in [
+                "",
+                "let [x] <- 1;",
+                "tell -> 3 d list [uneven] : = 2;",
+                "define [f]:\n    out -> 1;\n>>>>",
+                "define [f]:\n    if | 1 = 1 |:\n        out -> 1;\n    >>>>\n    out -> 2;\n>>>>",
+                "let [r] <- record (\n    [a] <- 1,\n    [b] <- 2\n);",
+            ] {
+                assert!(is_complete(src), "`{}`", src);
+            } // for ..
+
+            for src in [
+                "let [x] <- 1",
+                "define [f]:",
+                "define [f]:\n    out -> 1;",
+                "define [f]:\n    if | 1 = 1 |:\n        out -> 1;\n    >>>>",
+                "let [r] <- record (\n    [a] <- 1,",
+            ] {
+                assert!(!is_complete(src), "`{}`", src);
+            } // for ..
+        } // fn ..
+    } // mod ..
@@ -0,0 +1,198 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use crate::{
+        Expr, Val, Op, Pool, Keep,
+        SyntaxContext,
+        InterpreterErr,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// A parser of the standard dice notation, as in `4d6kh3 + 2`, `2d20kl1`, `3d6!`, `d%` or `4dF`.
+    struct Notation<'a> {
+        src:    &'a [u8],
+        cursor: usize,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl Expr {
+        /// Converts a roll written in standard dice notation into an expression.
+        pub fn from_notation(src: &str) -> Result<Self, InterpreterErr> {
+            Notation { src: src.as_bytes(), cursor: 0usize }.expr()
+        } // fn ..
+
+
+        /// Rolls the expression within a given context and outputs its integer result.
+        pub fn roll(&self, context: &mut SyntaxContext) -> Result<i16, InterpreterErr> { self.as_int(context) }
+    } // impl ..
+
+
+    impl Notation<'_> {
+        /// Peeks the current character, skipping any whitespace.
+        fn peek(&mut self) -> Option<u8> {
+            while self.src.get(self.cursor).is_some_and(u8::is_ascii_whitespace) { self.cursor += 1usize; }
+            self.src.get(self.cursor).copied()
+        } // fn ..
+
+
+        /// Returns an error pointing at the current character.
+        fn err(&self) -> InterpreterErr { InterpreterErr::BadNotation(self.cursor) }
+
+
+        /// Parses a sum of terms up to the end of the notation.
+        fn expr(&mut self) -> Result<Expr, InterpreterErr> {
+
+            let mut expr = Expr::Val(self.term()?);
+            loop {
+                expr = match self.peek() {
+                    Some(b'+') => { self.cursor += 1usize; Expr::Op(Op::Add(self.term()?, Box::new(expr))) },
+                    Some(b'-') => { self.cursor += 1usize; Expr::Op(Op::Sub(self.term()?, Box::new(expr))) },
+                    Some(_)    => break Err(self.err()),
+                    None       => break Ok(expr),
+                } // match ..
+            } // loop ..
+        } // fn ..
+
+
+        /// Parses a constant or a dice throw, which may be negated.
+        fn term(&mut self) -> Result<Val, InterpreterErr> {
+
+            if self.peek() == Some(b'-') {
+                self.cursor += 1usize;
+                return Ok(Val::Expr(Box::new(Expr::Op(Op::Neg(self.term()?)))))
+            } // if ..
+
+            self.peek();
+            let from = self.cursor;
+            let n    = self.int()?;
+            match self.peek() {
+                Some(b'd' | b'D') if n == Some(0i16) => Err(InterpreterErr::BadNotation(from)),
+                Some(b'd' | b'D') => { self.cursor += 1usize; self.dice(n.unwrap_or(1i16)) },
+                _                 => n.map(Val::Int).ok_or(self.err()),
+            } // match ..
+        } // fn ..
+
+
+        /// Parses the face and modifiers of a throw of `n` dice, fate dice being custom dice with faces `-1`, `0` and `1`.
+        fn dice(&mut self, n: i16) -> Result<Val, InterpreterErr> {
+
+            let face = match self.peek() {
+                Some(b'%')        => { self.cursor += 1usize; Val::Int(100i16) },
+                Some(b'F' | b'f') => { self.cursor += 1usize; Val::List((-1i16..=1i16).map(|face| Expr::Val(Val::Int(face))).collect()) },
+                _                 => match (self.cursor, self.int()?) {
+                    (from, Some(0i16) | None) => return Err(InterpreterErr::BadNotation(from)),
+                    (_, Some(face))           => Val::Int(face),
+                }, // => ..
+            }; // let ..
+
+            let mut pool = Pool { explode: false, keep: None };
+            loop {
+                match self.peek() {
+                    Some(b'!') if !pool.explode              => { self.cursor += 1usize; pool.explode = true; },
+                    Some(b'k' | b'K') if pool.keep.is_none() => {
+                        self.cursor += 1usize;
+                        let low = match self.peek() {
+                            Some(b'h' | b'H') => { self.cursor += 1usize; false },
+                            Some(b'l' | b'L') => { self.cursor += 1usize; true },
+                            _                 => false,
+                        }; // let ..
+                        let keep  = Val::Int(self.int()?.unwrap_or(1i16));
+                        pool.keep = Some(if low { Keep::Low(keep) } else { Keep::High(keep) });
+                    }, // => ..
+                    _ => break,
+                } // match ..
+            } // loop ..
+
+            let n = Box::new(Expr::Val(Val::Int(n)));
+            Ok(Val::Expr(Box::new(Expr::Op(match pool {
+                Pool { explode: false, keep: None } => Op::Dice(face, n, None),
+                pool                                => Op::Pool(face, n, Box::new(pool)),
+            })))) // Ok()
+        } // fn ..
+
+
+        /// Parses an eventual integer literal.
+        fn int(&mut self) -> Result<Option<i16>, InterpreterErr> {
+
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) { return Ok(None) }
+
+            let from = self.cursor;
+            while self.src.get(self.cursor).is_some_and(u8::is_ascii_digit) { self.cursor += 1usize; }
+            std::str::from_utf8(&self.src[from..self.cursor]).ok()
+                .and_then(|digits| digits.parse::<i16>().ok())
+                .map(Some)
+                .ok_or(InterpreterErr::BadNotation(from))
+        } // fn ..
+    } // impl ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{Expr, SyntaxContext, InterpreterErr};
+
+        /// Rolls a notation under many seeds, returning the lowest and highest results.
+        fn bounds(notation: &str) -> (i16, i16) {
+            let expr = Expr::from_notation(notation).unwrap();
+            (0u64..2000u64).fold((i16::MAX, i16::MIN), |(min, max), seed| {
+                let mut context = SyntaxContext::default();
+                context.set_seed(seed);
+                let roll = expr.roll(&mut context).unwrap();
+                (min.min(roll), max.max(roll))
+            }) // fold()
+        } // fn ..
+
+        #[test]
+        fn notations_roll_within_their_range() {
+            for (notation, expected) in [
+                ("3d6",         (3i16, 18i16)),
+                ("d%",          (1i16, 100i16)),
+                ("4dF",         (-4i16, 4i16)),
+                ("dF + 10",     (9i16, 11i16)),
+                ("4d6kh3",      (3i16, 18i16)),
+                ("2d20kl1",     (1i16, 20i16)),
+                ("2d6k",        (1i16, 6i16)),
+                ("1d6 + 2 - 1", (2i16, 7i16)),
+                ("-1d4",        (-4i16, -1i16)),
+                ("5 - 2",       (3i16, 3i16)),
+            ] {
+                assert_eq!(bounds(notation), expected, "`{}`", notation);
+            } // for ..
+
+            let (min, max) = bounds("1d2!");
+            assert!(min == 1i16 && max > 2i16, "exploding dice never rolled again");
+            assert!(bounds("3d6!kh2").0 >= 2i16);
+        } // fn ..
+
+        #[test]
+        fn malformed_notations_are_rejected() {
+            for (notation, at) in [
+                ("",         0usize),
+                ("d",        1usize),
+                ("d0",       1usize),
+                ("0d6",      0usize),
+                ("2 + 0d6",  4usize),
+                ("3d0",      2usize),
+                ("2d",       2usize),
+                ("1d6 +",    5usize),
+                ("1d6 x",    4usize),
+                ("4d6kq",    4usize),
+                ("99999d6",  0usize),
+                ("1d6!!",    4usize),
+            ] {
+                assert!(matches!(Expr::from_notation(notation), Err(InterpreterErr::BadNotation(found)) if found == at), "`{}`", notation);
+            } // for ..
+        } // fn ..
+    } // mod ..
@@ -0,0 +1,71 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use crate::{
+        Expr,
+        Primitive,
+        Span,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// Where the values told by the scripts of a context go.
+    pub trait Output: Send + Sync {
+        /// Receives the value told by a `tell` statement.
+        fn tell(&self, event: TellEvent);
+    } // trait ..
+
+
+    /// A value told by a `tell` statement.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TellEvent {
+        /// The value told.
+        pub value: Primitive,
+        /// The expression the value was evaluated from.
+        pub expr:  Expr,
+        /// Where the statement lies within the source it was pushed with.
+        pub span:  Span,
+        /// Every die thrown while evaluating the value, in order.
+        pub dice:  Vec<i16>,
+    } // struct ..
+
+
+    /// An output printing every told value on the standard output.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct StdOutput;
+
+
+    /// An output keeping every told value in memory, its clones sharing the same values.
+    #[derive(Debug, Default, Clone)]
+    pub struct Collector {
+        events: Arc<Mutex<Vec<TellEvent>>>,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl Output for StdOutput {
+        fn tell(&self, event: TellEvent) { println!("{:?}", event.value); }
+    } // impl ..
+
+
+    impl Output for Collector {
+        fn tell(&self, event: TellEvent) { self.events.lock().unwrap_or_else(PoisonError::into_inner).push(event); }
+    } // impl ..
+
+
+    impl Collector {
+        /// Returns every value told so far.
+        pub fn events(&self) -> Vec<TellEvent> { self.events.lock().unwrap_or_else(PoisonError::into_inner).clone() }
+
+        /// Returns and forgets every value told so far.
+        pub fn take(&self) -> Vec<TellEvent> { std::mem::take(&mut *self.events.lock().unwrap_or_else(PoisonError::into_inner)) }
+    } // impl ..
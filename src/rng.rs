@@ -0,0 +1,58 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::ops::RangeInclusive;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use serde::{Serialize, Deserialize};
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// A seedable xorshift generator, so that a run can be replayed from the seed it started with.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Rng {
+        state: u64,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl Default for Rng {
+        /// Creates a generator seeded from the current time.
+        fn default() -> Self {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos() as u64).unwrap_or_default();
+            Rng::seeded(nanos)
+        } // fn ..
+    } // impl ..
+
+
+    impl Rng {
+        /// Creates a generator from a given seed, any seed including zero being valid.
+        pub fn seeded(seed: u64) -> Self {
+            let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            Rng { state: (z ^ (z >> 31)) | 1u64 }
+        } // fn ..
+
+
+        fn next(&mut self) -> u64 {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            self.state
+        } // fn ..
+
+
+        /// Generates an integer lying in a given range, or its start when the range is empty.
+        pub(crate) fn generate_irange(&mut self, range: RangeInclusive<i16>) -> i16 {
+            let (start, end) = (*range.start() as i32, *range.end() as i32);
+            let span         = (end - start + 1i32).max(1i32) as u64;
+            (start + ((self.next() as u128 * span as u128) >> 64) as i32) as i16
+        } // fn ..
+    } // impl ..
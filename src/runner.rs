@@ -0,0 +1,127 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::cell::OnceCell;
+
+    use rusty_toolkit::LexingErr;
+
+    use crate::{
+        WsToken,
+        Stm, Expr, Val,
+        SyntaxContext, SyntaxElement,
+        InterpreterErr,
+        PrimitiveId,
+        Symbol,
+        TypeChecker, Program,
+        Output,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// Runs the `test_*` procedures of a script, each one in a copy of the context the script left, rolling the same dice.
+    pub struct TestRunner {
+        context: SyntaxContext,
+        seed:    u64,
+        prelude: OnceCell<SyntaxContext>,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl TestRunner {
+        /// The prefix a procedure name starts with to be run as a test.
+        pub const PREFIX: &'static str = "test_";
+
+
+        /// Lexes a script whose tests will all be seeded with a given seed.
+        pub fn new(src: &str, seed: u64) -> Result<Self, LexingErr> {
+            let mut context = SyntaxContext::default();
+            context.push(src)?;
+            Ok(TestRunner { context, seed, prelude: OnceCell::default() })
+        } // fn ..
+
+
+        /// Replaces the output the values told by the script and its tests go to.
+        pub fn set_output(&mut self, output: impl Output + 'static) { self.context.set_output(output); }
+
+
+        /// Checks and runs the script, then returns the name of every test it defines at its top level, in order.
+        pub fn discover(&self) -> Result<Vec<Symbol>, Vec<InterpreterErr>> {
+            let (_, stms) = self.load().map_err(|err| vec![err])?;
+            TypeChecker::new(&self.context).check(&stms)?;
+            self.prelude().map_err(|err| vec![err])?;
+
+            Ok(stms.iter().filter_map(|stm| match stm {
+                Stm::Define(_, name, params, _) if params.is_empty() && name.as_str().starts_with(Self::PREFIX) => Some(*name),
+                _                                                                                                => None,
+            }).collect())
+        } // fn ..
+
+
+        /// Runs a given test on the virtual machine, failing on the first error raised.
+        pub fn run(&self, name: &Symbol) -> Result<(), InterpreterErr> {
+            let mut context = self.prelude()?.clone();
+            let call = [Stm::Out(0usize, Expr::Val(Val::Call(PrimitiveId::Void, *name, Vec::default())))];
+            Program::compile(&context, &call).run(&mut context).map(|_| ())
+        } // fn ..
+
+
+        /// Returns the context the script leaves, running it on the virtual machine the first time only.
+        fn prelude(&self) -> Result<&SyntaxContext, InterpreterErr> {
+            if let Some(context) = self.prelude.get() { return Ok(context) }
+
+            let (mut context, stms) = self.load()?;
+            Program::compile(&context, &stms).run(&mut context)?;
+            Ok(self.prelude.get_or_init(|| context))
+        } // fn ..
+
+
+        /// Returns a fresh seeded context along with the statements of the script.
+        fn load(&self) -> Result<(SyntaxContext, Vec<Stm>), InterpreterErr> {
+            let mut context = self.context.clone();
+            context.set_seed(self.seed);
+            let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof)?;
+            Ok((context, stms))
+        } // fn ..
+    } // impl ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{TestRunner, InterpreterErr, Symbol, Collector};
+
+        #[test]
+        fn tests_run_after_the_script() {
+            let runner = TestRunner::new("define [roll]: out -> 1d6; >>>> define [test_pass]: assert -> integer [roll] + 1 > 1; >>>> define [test_fail]: assert -> proc [roll] > 10; >>>>", 42u64).unwrap();
+            assert_eq!(runner.discover().ok(), Some(vec![Symbol::from("test_pass"), Symbol::from("test_fail")]));
+
+            assert!(runner.run(&Symbol::from("test_pass")).is_ok());
+            assert!(matches!(runner.run(&Symbol::from("test_fail")), Err(InterpreterErr::AssertionFailed(..))));
+        } // fn ..
+
+        #[test]
+        fn the_script_runs_once() {
+            let told       = Collector::default();
+            let mut runner = TestRunner::new("tell -> 'loading'; let [n] <- 0; define [test_a]: set [n] <- integer [n] + 1; assert -> integer [n] = 1; >>>> define [test_b]: set [n] <- integer [n] + 1; assert -> integer [n] = 1; tell -> integer [n]; >>>>", 42u64).unwrap();
+            runner.set_output(told.clone());
+
+            let tests = runner.discover().unwrap();
+            assert_eq!(told.take().len(), 1usize);
+            for name in &tests { assert!(runner.run(name).is_ok(), "`{}`", name); }
+            assert_eq!(told.take().len(), 1usize);
+
+            let broken = TestRunner::new("let [x] <- 1 / 0; define [test_a]: assert -> 1 = 1; >>>>", 42u64).unwrap();
+            assert!(matches!(broken.discover(), Err(errs) if matches!(errs.as_slice(), [InterpreterErr::DivisionByZero])));
+            assert!(matches!(broken.run(&Symbol::from("test_a")), Err(InterpreterErr::DivisionByZero)));
+        } // fn ..
+    } // mod ..
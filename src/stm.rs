@@ -2,14 +2,21 @@
 // D E P E N D E N C I E S
 //#########################
 
-    use rusty_toolkit::{FixedStr, L16};
+    use std::iter;
+    use std::fmt;
+    use std::sync::Arc;
+
+    use serde::{Serialize, Deserialize};
 
     use crate::{
-        WsToken,
-        Expr,
+        WsToken, Span,
+        Expr, Op, Macro,
         SyntaxContext, SyntaxElement,
         InterpreterErr,
-        Primitive,
+        Val,
+        Primitive, PrimitiveId, Proc, Table,
+        Symbol,
+        TellEvent,
     }; // use ..
 
 
@@ -17,19 +24,67 @@
 // D E F I N I T I O N S
 //#######################
 
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)]
-    /// The statement enumerator.
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    /// The statement enumerator, each statement leading with the index of its first token.
     pub enum Stm {
-        RegLet (FixedStr<L16>, Decl),
-        RegSet (FixedStr<L16>, Decl),
-        While  (Expr, Decl),
-        If     (Expr, Decl),
-        Tell   (Expr),
-        Out    (Expr),
+        RegLet  (usize, Symbol, Decl),
+        RegSet  (usize, Symbol, Decl),
+        Define  (usize, Symbol, Vec<Symbol>, Decl),
+        Macro   (usize, Symbol, Vec<Symbol>, Expr),
+        Table   (usize, Symbol, Table),
+        ItemSet (usize, Symbol, Expr, Decl),
+        FieldSet(usize, Symbol, Vec<Symbol>, Decl),
+        Append  (usize, Symbol, Decl),
+        Remove  (usize, Symbol, Expr),
+        While   (usize, Expr, Decl),
+        ForEach (usize, PrimitiveId, Symbol, Expr, Decl),
+        For     (usize, Symbol, Bounds, Decl),
+        If      (usize, Expr, Decl),
+        Tell    (usize, Expr, Span),
+        Assert  (usize, Expr),
+        Mean    (usize, Sample, Bound, Bound),
+        Never   (usize, Sample),
+        Out     (usize, Expr),
     } // enum Stm
 
 
-    #[derive(Debug, Hash, PartialEq, Eq, Clone)]
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    /// The bounds of a counting loop, its step defaulting to one.
+    pub struct Bounds {
+        pub(crate) from:      Expr,
+        pub(crate) to:        Expr,
+        pub(crate) step:      Option<Expr>,
+        pub(crate) inclusive: bool,
+    } // struct ..
+
+
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    /// An expression evaluated over a given amount of runs, as statistical assertions do.
+    pub struct Sample {
+        pub(crate) runs: Expr,
+        pub(crate) expr: Expr,
+    } // struct ..
+
+
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    /// The target or the tolerance of a statistical assertion, written either as an integer expression or as a decimal literal.
+    pub enum Bound {
+        Expr(Expr),
+        Decimal(String),
+    } // enum ..
+
+
+    #[derive(Debug, Clone, PartialEq)]
+    /// The statistics observed over the runs of a sample.
+    pub struct Stats {
+        pub runs: usize,
+        pub mean: f64,
+        pub min:  i16,
+        pub max:  i16,
+    } // struct ..
+
+
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
     /// The declaration enumerator.
     pub enum Decl {
         Expr    (Expr),
@@ -51,38 +106,136 @@
             input:     Self::Input,
             end_token: WsToken,
         ) -> Result<Self::Output, InterpreterErr> {
-
-            if let Some(token) = context.peek() {
-                if token == &end_token { context.next(); Ok(input) } else {
-                    match match *context.peek().unwrap() {
-                        WsToken::Let   => { context.next(); let name = context.next_ref()?; let decl = context.next_decl(WsToken::InArrow)?;                                                       Ok(Some(Stm::RegLet(name, decl))) }
-                        WsToken::Set   => { context.next(); let name = context.next_ref()?; let decl = context.next_decl(WsToken::InArrow)?;                                                       Ok(Some(Stm::RegSet(name, decl))) }
-                        WsToken::Def   => { context.next(); let name = context.next_ref()?; let decl = context.next_decl(WsToken::InArrow)?; context.new_reg(&name, crate::Primitive::Proc(decl)); Ok(None) }
-                        WsToken::Tell  => { context.next(); let expr = context.next_expr(WsToken::OutArrow, WsToken::SemiColon)?;                                                                  Ok(Some(Stm::Tell(expr))) }
-                        WsToken::If    => { context.next(); let expr = context.next_expr(WsToken::Pipe, WsToken::Pipe)?; let decl = context.next_decl(WsToken::OutArrow)?;                         Ok(Some(Stm::If(expr, decl))) }
-                        WsToken::While => { context.next(); let expr = context.next_expr(WsToken::Pipe, WsToken::Pipe)?; let decl = context.next_decl(WsToken::OutArrow)?;                         Ok(Some(Stm::While(expr, decl))) }
-                        WsToken::Out   => { context.next(); let expr = context.next_expr(WsToken::OutArrow, WsToken::SemiColon)?;                                                                  Ok(Some(Stm::Out(expr))) }
-                        _              => Err(InterpreterErr::WrongToken(context.token_index(), context.peek().unwrap().clone())),
-                    }? {
-                        Some(stm) => { let mut output = input; output.push(stm); Self::scan(context, output, end_token) },
-                        None      => Ok(input),
-                    }
-                } // if ..
-            } else { Err(InterpreterErr::WrongEof(context.token_index())) } // if ..
+            context.nested(|context| context.macro_scope(|context| {
+                let mut output = input;
+                loop {
+                    match context.peek() {
+                        Some(token) if token == &end_token => { context.next(); break Ok(output) },
+                        Some(_) => {
+                            let index = context.token_index();
+                            output.push(Self::next_stm(context, index).inspect_err(|_| context.rewind(index))?);
+                        }, // => ..
+                        None => break Err(InterpreterErr::WrongEof(context.token_index())),
+                    } // match ..
+                } // loop ..
+            })) // nested()
         } // fn ..
     } // impl ..
 
 
     impl Stm {
-        /// Runs through a declaration to output an eventual expression.
-        pub fn run (
+        /// Scans the statement starting at the current token.
+        fn next_stm(context: &mut SyntaxContext, index: usize) -> Result<Self, InterpreterErr> {
+            match *context.peek().unwrap() {
+                WsToken::Let   => { context.next(); let name = context.next_ref()?; let decl = context.next_decl(WsToken::InArrow)?;                                      Ok(Stm::RegLet(index, name, decl)) }
+                WsToken::Set   => { context.next(); let (name, path) = context.next_path()?; match if path.is_empty() { context.next_index()? } else { None } {
+                    Some(at)                => { let decl = context.next_decl(WsToken::InArrow)?; Ok(Stm::ItemSet(index, name, at, decl)) },
+                    None if path.is_empty() => { let decl = context.next_decl(WsToken::InArrow)?; Ok(Stm::RegSet(index, name, decl)) },
+                    None                    => { let decl = context.next_decl(WsToken::InArrow)?; Ok(Stm::FieldSet(index, name, path, decl)) },
+                }}, // => ..
+                WsToken::Def   => { context.next(); let name = context.next_ref()?; let params = context.next_params()?; let decl = context.next_decl(WsToken::InArrow)?; Ok(Stm::Define(index, name, params, decl)) }
+                WsToken::Macro => {
+                    context.next();
+                    let name   = context.next_ref()?;
+                    let params = context.next_params()?;
+                    let body   = context.next_expr(WsToken::InArrow, WsToken::SemiColon)?;
+                    context.scan_macro(&name, Macro { params: params.clone(), body: body.clone() });
+                    Ok(Stm::Macro(index, name, params, body))
+                }, // => ..
+                WsToken::Tell  => { context.next(); let expr = context.next_expr(WsToken::OutArrow, WsToken::SemiColon)?; let span = context.span(index);                   Ok(Stm::Tell(index, expr, span)) }
+                WsToken::If    => { context.next(); let expr = context.next_expr(WsToken::Pipe, WsToken::Pipe)?; let decl = context.next_decl(WsToken::OutArrow)?;        Ok(Stm::If(index, expr, decl)) }
+                WsToken::While => { context.next(); let expr = context.next_expr(WsToken::Pipe, WsToken::Pipe)?; let decl = context.next_decl(WsToken::OutArrow)?;        Ok(Stm::While(index, expr, decl)) }
+                WsToken::Out   => { context.next(); let expr = context.next_expr(WsToken::OutArrow, WsToken::SemiColon)?;                                                 Ok(Stm::Out(index, expr)) }
+                WsToken::Assert => {
+                    context.next();
+                    match context.peek() {
+                        Some(WsToken::Mean) => {
+                            context.next();
+                            let runs      = context.next_expr(WsToken::Pipe, WsToken::Pipe)?;
+                            let expr      = context.next_expr(WsToken::OutArrow, WsToken::Eq)?;
+                            let target    = context.next_bound(WsToken::Within)?;
+                            let tolerance = context.next_bound(WsToken::SemiColon)?;
+                            Ok(Stm::Mean(index, Sample::new(runs, expr)?, target, tolerance))
+                        }, // => ..
+                        Some(WsToken::Never) => {
+                            context.next();
+                            let runs = context.next_expr(WsToken::Pipe, WsToken::Pipe)?;
+                            let expr = context.next_expr(WsToken::OutArrow, WsToken::SemiColon)?;
+                            Ok(Stm::Never(index, Sample::new(runs, expr)?))
+                        }, // => ..
+                        _ => {
+                            let expr = context.next_expr(WsToken::OutArrow, WsToken::SemiColon)?;
+                            Ok(Stm::Assert(index, expr))
+                        }, // => ..
+                    } // match ..
+                }, // => ..
+                WsToken::Append => {
+                    context.next();
+                    let name = context.next_ref()?;
+                    let decl = context.next_decl(WsToken::InArrow)?;
+                    Ok(Stm::Append(index, name, decl))
+                }, // => ..
+                WsToken::Remove => {
+                    context.next();
+                    let name = context.next_ref()?;
+                    let at   = context.next_index()?.ok_or(InterpreterErr::ExpectedAnExpr(context.token_index()))?;
+                    context.next_token(WsToken::SemiColon)?;
+                    Ok(Stm::Remove(index, name, at))
+                }, // => ..
+                WsToken::Table => {
+                    context.next();
+                    let name = context.next_ref()?;
+                    let dice = context.next_expr(WsToken::Pipe, WsToken::Pipe)?;
+                    context.next_token(WsToken::Colon)?;
+
+                    let mut rows = Vec::default();
+                    while context.peek() != Some(&WsToken::Eos) { rows.push(context.next_row()?); }
+                    context.next();
+
+                    Ok(Stm::Table(index, name, Table::new(index, dice, rows)?))
+                }, // => ..
+                WsToken::For if context.peek_nth(1usize) != Some(&WsToken::Each) => {
+                    context.next();
+                    let name   = context.next_ref()?;
+                    context.next_token(WsToken::In)?;
+                    let bounds = context.next_bounds()?;
+                    let decl   = context.next_decl(WsToken::OutArrow)?;
+                    Ok(Stm::For(index, name, bounds, decl))
+                }, // => ..
+                WsToken::For => {
+                    context.next();
+                    context.next_token(WsToken::Each)?;
+                    let id   = PrimitiveId::try_from(context.peek().unwrap())?;
+                    context.next();
+                    let name = context.next_ref()?;
+                    context.next_token(WsToken::In)?;
+                    let expr = context.next_expr(WsToken::Pipe, WsToken::Pipe)?;
+                    let decl = context.next_decl(WsToken::OutArrow)?;
+                    Ok(Stm::ForEach(index, id, name, expr, decl))
+                }, // => ..
+                _              => Err(InterpreterErr::WrongToken(context.token_index(), context.peek().unwrap().clone())),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the index of the first token of the statement.
+        pub fn index(&self) -> usize {
+            match self {
+                Stm::RegLet(index, ..) | Stm::RegSet(index, ..) | Stm::Define(index, ..) | Stm::Macro(index, ..) | Stm::Table(index, ..) | Stm::ItemSet(index, ..) | Stm::FieldSet(index, ..) | Stm::Append(index, ..) | Stm::Remove(index, ..) |
+                Stm::While(index, ..)  | Stm::ForEach(index, ..) | Stm::For(index, ..) | Stm::If(index, ..) | Stm::Tell(index, ..)   | Stm::Assert(index, ..) | Stm::Mean(index, ..) | Stm::Never(index, ..) | Stm::Out(index, ..) => *index,
+            } // match ..
+        } // fn ..
+
+
+        /// Runs through a declaration to output an eventual value.
+        pub fn run(
             context: &mut SyntaxContext,
             decl:    &Decl,
-        ) -> Result<Option<Expr>, InterpreterErr> {
+        ) -> Result<Option<Val>, InterpreterErr> {
             match decl {
-                Decl::Expr(expr)    => Ok(Some(expr.clone())),
+                Decl::Expr(expr)    => Ok(Some(expr.as_val(context)?)),
                 Decl::Closure(stms) => {
-                    match stms.iter().find_map(|stm| match stm.as_expr(context) {
+                    match stms.iter().find_map(|stm| match context.step().and_then(|_| stm.as_expr(context)) {
                         Ok(Some(out)) => Some(Ok(Some(out))),
                         Err(err)      => Some(Err(err)),
                         _             => None,
@@ -95,15 +248,291 @@
         } // fn ..
 
 
-        /// Returns an eventual expression out of a statement.
-        fn as_expr(&self, context: &mut SyntaxContext) -> Result<Option<Expr>, InterpreterErr> {
+        /// Runs a nested declaration, restoring the procedures and macros it defines once it outputs or runs out of statements.
+        pub(crate) fn scope(
+            context: &mut SyntaxContext,
+            decl:    &Decl,
+        ) -> Result<Option<Val>, InterpreterErr> {
+
+            let saved = decl.defines().into_iter()
+                .map(|name| (name, context.registers().get(&name).cloned()))
+                .collect::<Vec<(Symbol, Option<Primitive>)>>();
+
+            let macros = decl.macros().into_iter()
+                .map(|name| (name, context.find_macro(&name).cloned()))
+                .collect::<Vec<(Symbol, Option<Macro>)>>();
+
+            let output = Self::run(context, decl);
+            saved.into_iter().rev().for_each(|(name, value)| context.restore_reg(&name, value));
+            macros.into_iter().rev().for_each(|(name, value)| context.restore_macro(&name, value));
+            output
+        } // fn ..
+
+
+        /// Runs a statement and returns the value of its eventual output.
+        fn as_expr(&self, context: &mut SyntaxContext) -> Result<Option<Val>, InterpreterErr> {
+            match self {
+                Stm::RegLet(_, name, decl)         => { let value = Self::value(context, decl)?; match value { Primitive::Void => context.del_reg(name), _ =>  context.new_reg(name, value)?,  } Ok(None) },
+                Stm::RegSet(_, name, decl)         => { let value = Self::value(context, decl)?; match value { Primitive::Void => context.del_reg(name), _ => *context.reg_mut(name)? = value, } Ok(None) },
+                Stm::Define(_, name, params, decl) => { context.new_reg(name, Primitive::Proc(Proc { params: params.clone(), captures: Vec::default(), body: Arc::new(decl.clone()) }))?; Ok(None) },
+                Stm::Macro(_, name, params, body)  => { context.new_macro(name, Macro { params: params.clone(), body: body.clone() }); Ok(None) },
+                Stm::Table(_, name, table)         => { context.new_reg(name, Primitive::Table(table.clone()))?; Ok(None) },
+                Stm::ItemSet(_, name, at, decl)    => { let at = at.as_int(context)?; let value = Self::value(context, decl)?; *Primitive::item_mut(Self::list(context, name)?, at)? = value; Ok(None) },
+                Stm::FieldSet(_, name, path, decl) => { let value = Self::value(context, decl)?; *context.reg_mut(name)?.field_mut(path)? = value; Ok(None) },
+                Stm::Append(_, name, decl)         => { let value = Self::value(context, decl)?; Self::list(context, name)?.push(value); Ok(None) },
+                Stm::Remove(_, name, at)           => { let at = at.as_int(context)?; let items = Self::list(context, name)?; Primitive::item(items, at)?; items.remove(at as usize); Ok(None) },
+                Stm::If(_, expr, decl)             => { if Expr::Val(expr.as_val(context)?).as_bool(context)? { Self::scope(context, decl) } else { Ok(None) }}
+                Stm::While(_, expr, decl)          => { while Expr::Val(expr.as_val(context)?).as_bool(context)? { context.iterate()?; Self::scope(context, decl)?; }  Ok(None) }
+                Stm::ForEach(_, id, name, expr, decl) => {
+                    let items = expr.items(context)?;
+                    let saved = context.registers().get(name).cloned();
+
+                    let output = items.into_iter().try_for_each(|item| {
+                        match Val::typed(context, id, item)?.as_primitive(context)? {
+                            Primitive::Void => context.del_reg(name),
+                            value           => context.new_reg(name, value)?,
+                        } // match ..
+
+                        context.iterate()?;
+                        Self::scope(context, decl).map(|_| ())
+                    }); // let ..
+
+                    context.restore_reg(name, saved);
+                    output.map(|_| None)
+                }, // => ..
+                Stm::For(_, name, bounds, decl) => {
+                    let from = bounds.from.as_int(context)?;
+                    let to   = bounds.to.as_int(context)?;
+                    let step = match &bounds.step { Some(step) => step.as_int(context)?, None => 1i16 };
+                    if step == 0i16 { return Err(InterpreterErr::ZeroStep) }
+
+                    let saved  = context.registers().get(name).cloned();
+                    let output = iter::successors(Some(from), |i| i.checked_add(step))
+                        .take_while(|i| Bounds::covers(bounds.inclusive, *i, to, step))
+                        .try_for_each(|i| {
+                            context.new_reg(name, Primitive::Int(i))?;
+                            context.iterate()?;
+                            Self::scope(context, decl).map(|_| ())
+                        }); // let ..
+
+                    context.restore_reg(name, saved);
+                    output.map(|_| None)
+                }, // => ..
+                Stm::Out(_, expr)                  => { Ok(Some(expr.as_val(context)?)) },
+                Stm::Tell(_, expr, span)           => {
+                    let from  = context.open_tally();
+                    let value = expr.as_val(context)?.unwraped(context)?.as_primitive(context)?;
+                    let dice  = context.close_tally(from);
+                    context.tell(TellEvent { value, expr: expr.clone(), span: *span, dice });
+                    Ok(None)
+                }, // => ..
+                Stm::Assert(index, expr)           => { if Expr::Val(expr.as_val(context)?).as_bool(context)? { Ok(None) } else { Err(InterpreterErr::AssertionFailed(*index, Box::new(expr.clone()))) }},
+                Stm::Mean(index, sample, target, tolerance) => {
+                    let stats     = Stats::of(&sample.run(context, |context, expr| expr.as_int(context))?);
+                    let target    = target.value(context)?;
+                    let tolerance = tolerance.value(context)?;
+                    if stats.within(target, tolerance) { Ok(None) } else { Err(InterpreterErr::MeanOutOfRange(*index, Box::new(sample.expr.clone()), Box::new(stats), target, tolerance)) }
+                }, // => ..
+                Stm::Never(index, sample) => {
+                    let outcomes = sample.run(context, |context, expr| Expr::Val(expr.as_val(context)?).as_bool(context))?;
+                    match outcomes.iter().filter(|outcome| **outcome).count() {
+                        0usize => Ok(None),
+                        count  => Err(InterpreterErr::OutcomeProduced(*index, Box::new(sample.expr.clone()), count, outcomes.len())),
+                    } // match ..
+                }, // => ..
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Stm {
+        /// Runs a declaration and returns the primitive it outputs.
+        fn value(context: &mut SyntaxContext, decl: &Decl) -> Result<Primitive, InterpreterErr> {
+            match Self::scope(context, decl)? {
+                Some(val)  => val.as_primitive(context),
+                None       => Ok(Primitive::Void),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the items of the list stored in a given register.
+        fn list<'a>(context: &'a mut SyntaxContext, name: &Symbol) -> Result<&'a mut Vec<Primitive>, InterpreterErr> {
+            match context.reg_mut(name)? {
+                Primitive::List(items) => Ok(items),
+                _                      => Err(InterpreterErr::NotAList),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Bounds {
+        /// Tells whether a counter moving by a given step has not gone past the end of the loop yet.
+        pub(crate) fn covers(inclusive: bool, counter: i16, to: i16, step: i16) -> bool {
+            match (step > 0i16, inclusive) {
+                (true,  true)  => counter <= to,
+                (true,  false) => counter <  to,
+                (false, true)  => counter >= to,
+                (false, false) => counter >  to,
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Sample {
+        /// Creates a sample, rejecting an amount of runs written as a literal that is not positive.
+        pub(crate) fn new(runs: Expr, expr: Expr) -> Result<Self, InterpreterErr> {
+            let mut literal = &runs;
+            while let Expr::Val(Val::Expr(expr)) = literal { literal = expr }
+
+            match literal {
+                Expr::Val(Val::Int(n)) if *n <= 0i16 => Err(InterpreterErr::NoRuns(*n)),
+                Expr::Op(Op::Neg(Val::Int(n)))       => Err(InterpreterErr::NoRuns(-*n)),
+                _                                    => Ok(Sample { runs, expr }),
+            } // match ..
+        } // fn ..
+
+
+        /// Evaluates the expression once per run, reading each outcome with a given function.
+        pub(crate) fn run<T>(
+            &self,
+            context: &mut SyntaxContext,
+            read:    impl Fn(&mut SyntaxContext, &Expr) -> Result<T, InterpreterErr>,
+        ) -> Result<Vec<T>, InterpreterErr> {
+            let runs = self.runs.as_int(context)?;
+            if runs <= 0i16 { return Err(InterpreterErr::NoRuns(runs)) }
+            (0i16..runs).map(|_| { context.iterate()?; read(context, &self.expr) }).collect()
+        } // fn ..
+    } // impl ..
+
+
+    impl Bound {
+        /// Evaluates the bound, a decimal literal standing for itself.
+        pub(crate) fn value(&self, context: &mut SyntaxContext) -> Result<f64, InterpreterErr> {
+            match self {
+                Bound::Expr(expr)       => Ok(expr.as_int(context)? as f64),
+                Bound::Decimal(decimal) => Ok(decimal.parse::<f64>().unwrap_or_default()),
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Stats {
+        /// Computes the statistics of a list of outcomes, the mean of no outcome being undefined.
+        pub(crate) fn of(outcomes: &[i16]) -> Self {
+            Stats {
+                runs: outcomes.len(),
+                mean: outcomes.iter().map(|outcome| *outcome as f64).sum::<f64>() / outcomes.len() as f64,
+                min:  outcomes.iter().copied().min().unwrap_or_default(),
+                max:  outcomes.iter().copied().max().unwrap_or_default(),
+            } // Stats ..
+        } // fn ..
+
+
+        /// Tells whether the mean lies within a given tolerance of a target.
+        pub(crate) fn within(&self, target: f64, tolerance: f64) -> bool { (self.mean - target).abs() <= tolerance }
+    } // impl ..
+
+
+    impl fmt::Display for Stats {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "mean `{:.2}`, min `{}` and max `{}` over `{}` runs", self.mean, self.min, self.max, self.runs)
+        } // fn ..
+    } // impl ..
+
+
+    impl Decl {
+        /// Returns the name of every procedure defined by the statements of the declaration itself.
+        pub(crate) fn defines(&self) -> Vec<Symbol> {
             match self {
-                Stm::RegLet(name, decl) => { let expr = Self::run(context, decl).unwrap().unwrap(); let value = expr.as_val(context)?.as_primitive(context)?; match value { Primitive::Void => context.del_reg(name), _ =>  context.new_reg(name, value),   } Ok(None) },
-                Stm::RegSet(name, decl) => { let expr = Self::run(context, decl).unwrap().unwrap(); let value = expr.as_val(context)?.as_primitive(context)?; match value { Primitive::Void => context.del_reg(name), _ => *context.reg_mut(name)? = value, } Ok(None) },
-                Stm::If(expr, decl)     => { if Expr::Val(expr.as_val(context)?).as_bool(context)? { Self::run(context, decl) } else { Ok(None) }}
-                Stm::While(expr, decl)  => { while Expr::Val(expr.as_val(context)?).as_bool(context)? { Self::run(context, decl)?; }  Ok(None) }
-                Stm::Out(expr)          => { Ok(Some(expr.clone())) },
-                Stm::Tell(expr)         => { println!("{:?}", expr.as_val(context)?.unwraped(context)?.as_primitive(context)?); Ok(None) },
+                Decl::Closure(stms) => stms.iter().fold(Vec::default(), |mut names, stm| {
+                    if let Stm::Define(_, name, ..) = stm { if !names.contains(name) { names.push(*name) } }
+                    names
+                }), // => ..
+                Decl::Expr(_)       => Vec::default(),
             } // match ..
         } // fn ..
+
+
+        /// Returns the name of every macro defined by the statements of the declaration itself.
+        pub(crate) fn macros(&self) -> Vec<Symbol> {
+            match self {
+                Decl::Closure(stms) => stms.iter().fold(Vec::default(), |mut names, stm| {
+                    if let Stm::Macro(_, name, ..) = stm { if !names.contains(name) { names.push(*name) } }
+                    names
+                }), // => ..
+                Decl::Expr(_)       => Vec::default(),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the name of every register referred to in the declaration.
+        pub(crate) fn refs(&self) -> Vec<Symbol> {
+
+            fn stm_refs(stm: &Stm, refs: &mut Vec<Symbol>) {
+                match stm {
+                    Stm::RegLet(_, _, decl) | Stm::RegSet(_, _, decl) | Stm::Define(_, _, _, decl)          => decl_refs(decl, refs),
+                    Stm::ItemSet(_, name, at, decl)                                                         => { name_refs(name, refs); expr_refs(at, refs); decl_refs(decl, refs) },
+                    Stm::Table(_, _, table)                                                                 => { expr_refs(&table.dice, refs); table.rows.iter().for_each(|(_, _, decl)| decl_refs(decl, refs)) },
+                    Stm::Append(_, name, decl) | Stm::FieldSet(_, name, _, decl)                            => { name_refs(name, refs); decl_refs(decl, refs) },
+                    Stm::Remove(_, name, at)                                                                => { name_refs(name, refs); expr_refs(at, refs) },
+                    Stm::If(_, expr, decl)  | Stm::While(_, expr, decl) | Stm::ForEach(_, _, _, expr, decl) => { expr_refs(expr, refs); decl_refs(decl, refs) },
+                    Stm::For(_, _, bounds, decl)                                                            => { expr_refs(&bounds.from, refs); expr_refs(&bounds.to, refs); if let Some(step) = &bounds.step { expr_refs(step, refs) } decl_refs(decl, refs) },
+                    Stm::Tell(_, expr, _)   | Stm::Assert(_, expr)  | Stm::Out(_, expr)                     => expr_refs(expr, refs),
+                    Stm::Macro(..)                                                                          => (),
+                    Stm::Mean(_, sample, target, tolerance)                                                 => { expr_refs(&sample.runs, refs); expr_refs(&sample.expr, refs); [target, tolerance].into_iter().for_each(|bound| if let Bound::Expr(expr) = bound { expr_refs(expr, refs) }) },
+                    Stm::Never(_, sample)                                                                   => { expr_refs(&sample.runs, refs); expr_refs(&sample.expr, refs) },
+                } // match ..
+            } // fn ..
+
+            fn decl_refs(decl: &Decl, refs: &mut Vec<Symbol>) {
+                match decl {
+                    Decl::Expr(expr)    => expr_refs(expr, refs),
+                    Decl::Closure(stms) => stms.iter().for_each(|stm| stm_refs(stm, refs)),
+                } // match ..
+            } // fn ..
+
+            fn expr_refs(expr: &Expr, refs: &mut Vec<Symbol>) {
+
+                enum Node<'e> { Expr(&'e Expr), Val(&'e Val) }
+
+                let mut nodes = vec![Node::Expr(expr)];
+                while let Some(node) = nodes.pop() {
+                    match node {
+                        Node::Val(Val::Expr(b))   => nodes.push(Node::Expr(b)),
+                        Node::Val(a)              => val_refs(a, refs),
+                        Node::Expr(Expr::Val(a))  => nodes.push(Node::Val(a)),
+                        Node::Expr(Expr::Op(opr)) => match opr {
+                            Op::Neg(a)    | Op::Len(a)                                    => nodes.push(Node::Val(a)),
+                            Op::Add(a, b) | Op::Sub(a, b) | Op::Mul(a, b) | Op::Div(a, b) => nodes.extend([Node::Expr(b), Node::Val(a)]),
+                            Op::Dice(a, b, c)                                             => { if let Some(c) = c { nodes.push(Node::Val(c.val())) } nodes.extend([Node::Expr(b), Node::Val(a)]) },
+                            Op::Pool(a, b, c)                                             => { if let Some(c) = c.keep() { nodes.push(Node::Val(c)) } nodes.extend([Node::Expr(b), Node::Val(a)]) },
+                            Op::Cmp(c, b)                                                 => nodes.extend([Node::Expr(b), Node::Val(c.val())]),
+                        }, // => ..
+                    } // match ..
+                } // while ..
+            } // fn ..
+
+            fn val_refs(val: &Val, refs: &mut Vec<Symbol>) {
+                match val {
+                    Val::Ref(_, name)        |
+                    Val::Field(_, name, _)   => name_refs(name, refs),
+                    Val::Call(_, name, args) => { name_refs(name, refs); args.iter().for_each(|arg| expr_refs(arg, refs)) },
+                    Val::Lambda(_, decl)     => decl_refs(decl, refs),
+                    Val::List(items)         => items.iter().for_each(|item| expr_refs(item, refs)),
+                    Val::Record(fields)      => fields.iter().for_each(|(_, field)| expr_refs(field, refs)),
+                    Val::Expr(expr)          => expr_refs(expr, refs),
+                    _                        => (),
+                } // match ..
+            } // fn ..
+
+
+            fn name_refs(name: &Symbol, refs: &mut Vec<Symbol>) {
+                if !refs.contains(name) { refs.push(*name) }
+            } // fn ..
+
+
+            let mut refs = Vec::default();
+            decl_refs(self, &mut refs);
+            refs
+        } // fn ..
     } // impl ..
@@ -0,0 +1,103 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::collections::HashSet;
+    use std::fmt;
+    use std::hash::{Hash, Hasher};
+    use std::ptr;
+    use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// An interned identifier of any length, cheap to copy, hash and compare, and read without locking.
+    #[derive(Clone, Copy)]
+    pub struct Symbol(&'static str);
+
+
+    /// The table every identifier is interned in, each one being stored once for the whole run.
+    #[derive(Default)]
+    struct Interner {
+        strings: HashSet<&'static str>,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl Interner {
+        /// Locks the interner shared by every symbol.
+        fn get() -> MutexGuard<'static, Interner> {
+            static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+            INTERNER.get_or_init(Mutex::default).lock().unwrap_or_else(PoisonError::into_inner)
+        } // fn ..
+    } // impl ..
+
+
+    impl Symbol {
+        /// Returns the symbol of a given identifier, interning it on first use.
+        pub fn intern(string: &str) -> Self {
+
+            let mut interner = Interner::get();
+            if let Some(string) = interner.strings.get(string) { return Symbol(string) }
+
+            let string = Box::leak(string.to_owned().into_boxed_str());
+            interner.strings.insert(string);
+            Symbol(string)
+        } // fn ..
+
+
+        /// Returns the symbol of a given identifier if it was already interned, leaving the interner untouched otherwise.
+        pub fn lookup(string: &str) -> Option<Self> { Interner::get().strings.get(string).map(|string| Symbol(string)) }
+
+
+        /// Returns the string the symbol stands for.
+        pub fn as_str(&self) -> &'static str { self.0 }
+    } // impl ..
+
+
+    impl PartialEq for Symbol {
+        fn eq(&self, other: &Self) -> bool { ptr::eq(self.0, other.0) }
+    } // impl ..
+
+    impl Eq for Symbol {}
+
+
+    impl Hash for Symbol {
+        fn hash<H: Hasher>(&self, state: &mut H) { ptr::hash(self.0, state) }
+    } // impl ..
+
+
+    impl From<&str> for Symbol {
+        fn from(value: &str) -> Self { Symbol::intern(value) }
+    } // impl ..
+
+
+    impl AsRef<str> for Symbol {
+        fn as_ref(&self) -> &str { self.as_str() }
+    } // impl ..
+
+
+    impl fmt::Display for Symbol {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.as_str()) }
+    } // impl ..
+
+
+    impl fmt::Debug for Symbol {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:?}", self.as_str()) }
+    } // impl ..
+
+
+    impl Serialize for Symbol {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> { serializer.serialize_str(self.as_str()) }
+    } // impl ..
+
+
+    impl<'de> Deserialize<'de> for Symbol {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> { String::deserialize(deserializer).map(|string| Symbol::from(string.as_str())) }
+    } // impl ..
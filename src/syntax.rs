@@ -5,18 +5,25 @@
     use std::error::Error;
     use std::fmt::Display;
     use std::fmt;
+    use std::borrow::Cow;
     use std::collections::HashMap;
-    use rusty_toolkit::{
-        FixedStr, L16,
-        Lexer, LexingErr,
-        RandBitField,
-    }; // use ..
+    use std::fs::File;
+    use std::io::{self, Read, Write, BufReader, BufWriter};
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use rusty_toolkit::LexingErr;
 
     use crate::{
-        WsToken,
-        Stm, Decl, Expr,
-        Primitive,
+        WsToken, Span,
+        Stm, Decl, Expr, Val, Bounds, Bound, Stats, Macro,
+        Primitive, PrimitiveId,
+        Symbol, Rng, Host, Capture,
+        Output, StdOutput, TellEvent,
+        Input, StdInput,
     }; // use ..
+    use crate::input::read_answer;
+    use serde::{Serialize, Deserialize};
 
 
 //#######################
@@ -24,11 +31,72 @@
 //#######################
 
     /// A struct that contains the current context.
+    #[derive(Clone)]
     pub struct SyntaxContext {
-                   registers:      HashMap<FixedStr<L16>, Primitive>,
-        pub(crate) rand_bit_field: RandBitField<u16>,
-                   token_index:    usize,
-                   next_tokens:    Vec<WsToken>,
+                   registers:  HashMap<Symbol, Primitive>,
+        pub(crate) rng:        Rng,
+                   macros:     HashMap<Symbol, Macro>,
+                   scanned:    Vec<(Symbol, Macro)>,
+                   hosts:      HashMap<Symbol, Host>,
+                   output:     Arc<dyn Output>,
+                   input:      Arc<dyn Input>,
+                   tally:      Tally,
+                   tokens:     Vec<WsToken>,
+                   spans:      Vec<Span>,
+                   cursor:     usize,
+                   limits:     Limits,
+                   usage:      Usage,
+                   call_depth: usize,
+                   nesting:    usize,
+    } // struct ..
+
+
+    /// The limits a context enforces on the scripts it runs.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Limits {
+        /// The maximum amount of nested procedure calls.
+        pub call_depth: usize,
+        /// The maximum depth of nested expressions and blocks a script may be written with, kept low enough for every pass to fit a small thread stack.
+        pub nesting:    usize,
+        /// The maximum amount of executed statements.
+        pub statements: usize,
+        /// The maximum amount of thrown dice.
+        pub dice:       usize,
+        /// The maximum amount of loop iterations.
+        pub iterations: usize,
+        /// The maximum amount of allocated registers.
+        pub registers:  usize,
+        /// The maximum amount of rejected answers to a single question.
+        pub retries:    usize,
+        /// The maximum time a run may last.
+        pub timeout:    Option<Duration>,
+    } // struct ..
+
+
+    /// The resources consumed since the last usage reset.
+    #[derive(Debug, Clone)]
+    struct Usage {
+        statements: usize,
+        dice:       usize,
+        iterations: usize,
+        started:    Instant,
+    } // struct ..
+
+
+    /// The dice thrown while evaluating the values being told.
+    #[derive(Debug, Clone, Default)]
+    struct Tally {
+        depth: usize,
+        dice:  Vec<i16>,
+    } // struct ..
+
+
+    /// The part of a context a session can be resumed from: its registers, macros and dice.
+    #[derive(Serialize, Deserialize)]
+    struct Snapshot<'a> {
+        registers: Vec<(Symbol, Cow<'a, Primitive>)>,
+        macros:    Vec<(Symbol, Cow<'a, Macro>)>,
+        rng:       Cow<'a, Rng>,
     } // struct ..
 
 
@@ -49,14 +117,47 @@
     impl Display for InterpreterErr {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             write!(f, "{}", match self {
-                InterpreterErr::WrongToken(i, t)      => format!("Wrong token `{}` found at index `{}`!", t, i),
-                InterpreterErr::WrongEof(i)           => format!("Wrong end of file at index `{}`!", i),
-                InterpreterErr::ExpectedARef(i)       => format!("Expected a reference at index `{}`!", i),
-                InterpreterErr::ExpectedAnExpr(i)     => format!("Expected an expression at index `{}`!", i),
-                InterpreterErr::ExpectedADecl(i)      => format!("Expected a declaration at index `{}`!", i),
-                InterpreterErr::ExpectedAStm(i)       => format!("Expected a statement at index `{}`!", i),
-                InterpreterErr::FailedToReadPrimitive => format!("Failed to read a primitive keyword!"),
-                InterpreterErr::UninitReg(n)          => format!("Uninitialised register with name `{}`!", n),
+                InterpreterErr::WrongToken(i, t)        => format!("Wrong token `{}` found at index `{}`!", t, i),
+                InterpreterErr::WrongEof(i)             => format!("Wrong end of file at index `{}`!", i),
+                InterpreterErr::ExpectedARef(i)         => format!("Expected a reference at index `{}`!", i),
+                InterpreterErr::ExpectedAnExpr(i)       => format!("Expected an expression at index `{}`!", i),
+                InterpreterErr::ExpectedADecl(i)        => format!("Expected a declaration at index `{}`!", i),
+                InterpreterErr::ExpectedAStm(i)         => format!("Expected a statement at index `{}`!", i),
+                InterpreterErr::FailedToReadPrimitive   => String::from("Failed to read a primitive keyword!"),
+                InterpreterErr::UninitReg(n)            => format!("Uninitialised register with name `{}`!", n),
+                InterpreterErr::UndeclaredReg(i, n)     => format!("Register with name `{}` may be uninitialised at index `{}`!", n, i),
+                InterpreterErr::MismatchedType(i, e, f) => format!("Expected `{:?}` but found `{:?}` at index `{}`!", e, f, i),
+                InterpreterErr::MismatchedValue(e, f)   => format!("Expected a value of type `{}` but found one of type `{}`!", e, f),
+                InterpreterErr::CallDepthExceeded(d)    => format!("Exceeded the maximum call depth of `{}`!", d),
+                InterpreterErr::NestingExceeded(d)      => format!("Exceeded the maximum nesting depth of `{}`!", d),
+                InterpreterErr::StatementsExceeded(n)   => format!("Exceeded the maximum amount of `{}` executed statements!", n),
+                InterpreterErr::DiceExceeded(n)         => format!("Exceeded the maximum amount of `{}` thrown dice!", n),
+                InterpreterErr::IterationsExceeded(n)   => format!("Exceeded the maximum amount of `{}` loop iterations!", n),
+                InterpreterErr::RegistersExceeded(n)    => format!("Exceeded the maximum amount of `{}` allocated registers!", n),
+                InterpreterErr::RetriesExceeded(n)      => format!("Exceeded the maximum amount of `{}` rejected answers!", n),
+                InterpreterErr::TimedOut(t)             => format!("Exceeded the maximum run time of `{:?}`!", t),
+                InterpreterErr::WrongArity(e, f)        => format!("Expected `{}` arguments but found `{}`!", e, f),
+                InterpreterErr::NotAProc                => String::from("Expected a procedure!"),
+                InterpreterErr::NotAList                => String::from("Expected a list!"),
+                InterpreterErr::NotAText                => String::from("Expected a text!"),
+                InterpreterErr::NotATable               => String::from("Expected a table!"),
+                InterpreterErr::NotARecord              => String::from("Expected a record!"),
+                InterpreterErr::ZeroStep                => String::from("Expected a non-zero loop step!"),
+                InterpreterErr::DivisionByZero          => String::from("Attempted to divide by zero!"),
+                InterpreterErr::Overflow                => format!("Exceeded the integer range of `{}` to `{}`!", i16::MIN, i16::MAX),
+                InterpreterErr::NoRuns(n)               => format!("Expected a positive amount of runs but found `{}`!", n),
+                InterpreterErr::OverlappingRows(i, r)   => format!("Table rows overlap on roll `{}` at index `{}`!", r, i),
+                InterpreterErr::MissingRows(i, r)       => format!("Table rows leave roll `{}` uncovered at index `{}`!", r, i),
+                InterpreterErr::NoTableRow(r)           => format!("No table row covers roll `{}`!", r),
+                InterpreterErr::UnknownField(n)         => format!("Unknown record field with name `{}`!", n),
+                InterpreterErr::DuplicateField(i, n)    => format!("Duplicate record field with name `{}` at index `{}`!", n, i),
+                InterpreterErr::IndexOutOfBounds(i, l)  => format!("Index `{}` is out of bounds for a list of length `{}`!", i, l),
+                InterpreterErr::BadNotation(i)          => format!("Invalid dice notation at character `{}`!", i),
+                InterpreterErr::HostFailed(m)           => format!("Host function failed with `{}`!", m),
+                InterpreterErr::NoAnswer(p)             => format!("No answer was given to `{}`!", p),
+                InterpreterErr::AssertionFailed(i, e)   => format!("Assertion `{}` failed at index `{}`!", e, i),
+                InterpreterErr::MeanOutOfRange(i, e, s, t, d) => format!("Mean of `{}` is not within `{}` of `{}` at index `{}`, with {}!", e, d, t, i, s),
+                InterpreterErr::OutcomeProduced(i, e, c, n)   => format!("Outcome `{}` was produced `{}` times over `{}` runs at index `{}`!", e, c, n, i),
             }) // write()
         } // fn ..
     } // impl ..
@@ -70,7 +171,40 @@
         ExpectedAnExpr        (usize),
         ExpectedADecl         (usize),
         ExpectedAStm          (usize),
-        UninitReg             (FixedStr<L16>),
+        UninitReg             (Symbol),
+        UndeclaredReg         (usize, Symbol),
+        MismatchedType        (usize, PrimitiveId, PrimitiveId),
+        MismatchedValue       (PrimitiveId, PrimitiveId),
+        CallDepthExceeded     (usize),
+        NestingExceeded       (usize),
+        StatementsExceeded    (usize),
+        DiceExceeded          (usize),
+        IterationsExceeded    (usize),
+        RegistersExceeded     (usize),
+        RetriesExceeded       (usize),
+        TimedOut              (Duration),
+        WrongArity            (usize, usize),
+        IndexOutOfBounds      (i16, usize),
+        OverlappingRows       (usize, i16),
+        MissingRows           (usize, i16),
+        NoTableRow            (i16),
+        UnknownField          (Symbol),
+        DuplicateField        (usize, Symbol),
+        BadNotation           (usize),
+        HostFailed            (String),
+        NoAnswer              (String),
+        AssertionFailed       (usize, Box<Expr>),
+        MeanOutOfRange        (usize, Box<Expr>, Box<Stats>, f64, f64),
+        OutcomeProduced       (usize, Box<Expr>, usize, usize),
+        NotAProc,
+        NotAList,
+        NotAText,
+        NotATable,
+        NotARecord,
+        ZeroStep,
+        DivisionByZero,
+        Overflow,
+        NoRuns                (i16),
         FailedToReadPrimitive,
     } // enum ..
 
@@ -82,36 +216,330 @@
     impl Default for SyntaxContext {
         fn default() -> Self {
             SyntaxContext {
-                registers:      HashMap::default(),
-                rand_bit_field: RandBitField::default(),
-                token_index:    0usize,
-                next_tokens:    Vec::default(),
+                registers:  HashMap::default(),
+                rng:        Rng::default(),
+                macros:     HashMap::default(),
+                scanned:    Vec::default(),
+                hosts:      HashMap::default(),
+                output:     Arc::new(StdOutput),
+                input:      Arc::new(StdInput),
+                tally:      Tally::default(),
+                tokens:     Vec::default(),
+                spans:      Vec::default(),
+                cursor:     0usize,
+                limits:     Limits::default(),
+                usage:      Usage::default(),
+                call_depth: 0usize,
+                nesting:    0usize,
             } // SyntaxContext ..
         } // fn ..
     } // impl ..
 
 
+    impl Default for Limits {
+        fn default() -> Self {
+            Limits {
+                call_depth: 256usize,
+                nesting:    40usize,
+                statements: usize::MAX,
+                dice:       usize::MAX,
+                iterations: usize::MAX,
+                registers:  usize::MAX,
+                retries:    16usize,
+                timeout:    None,
+            } // Limits ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Limits {
+        /// Returns limits suited to scripts that can't be trusted, such as ones sent by the players of a game: every budget is bounded and a run lasts one second at most.
+        pub fn untrusted() -> Self {
+            Limits {
+                call_depth: 64usize,
+                statements: 1_000_000usize,
+                dice:       1_000_000usize,
+                iterations: 1_000_000usize,
+                registers:  10_000usize,
+                retries:    4usize,
+                timeout:    Some(Duration::from_secs(1u64)),
+                ..Limits::default()
+            } // Limits ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Default for Usage {
+        fn default() -> Self {
+            Usage {
+                statements: 0usize,
+                dice:       0usize,
+                iterations: 0usize,
+                started:    Instant::now(),
+            } // Usage ..
+        } // fn ..
+    } // impl ..
+
+
     impl SyntaxContext {
         /// The index of the last peeked token since the beginning of the context.
-        pub(crate) fn token_index(&self) -> usize { self.token_index }
+        pub(crate) fn token_index(&self) -> usize { self.cursor }
 
         /// Extends the context with newly lexed tokens.
-        pub fn push(&mut self, src: &str) -> Result<(), LexingErr> { Ok(self.next_tokens.extend_from_slice(&Lexer::run(src)?)) }
+        pub fn push(&mut self, src: &str) -> Result<(), LexingErr> {
+            let (tokens, spans) = WsToken::lex(src)?;
+            self.tokens.extend(tokens);
+            self.spans.extend(spans);
+            Ok(())
+        } // fn ..
+
+        /// Returns the limits enforced by the context.
+        pub fn limits(&self) -> &Limits { &self.limits }
+
+        /// Replaces the limits enforced by the context.
+        pub fn set_limits(&mut self, limits: Limits) { self.limits = limits; }
+
+        /// Reseeds the dice of the context, making every following roll reproducible.
+        pub fn set_seed(&mut self, seed: u64) { self.rng = Rng::seeded(seed); }
+
+        /// Writes the registers, macros and dice of the context, so that a session can be resumed later on, closures sharing a capture still sharing it once read.
+        pub fn write_state(&self, writer: impl Write) -> io::Result<()> {
+            let mut registers = self.registers.iter().map(|(name, value)| (*name, Cow::Borrowed(value))).collect::<Vec<_>>();
+            let mut macros    = self.macros.iter().map(|(name, value)| (*name, Cow::Borrowed(value))).collect::<Vec<_>>();
+            registers.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            macros.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+            let snapshot = Snapshot { registers, macros, rng: Cow::Borrowed(&self.rng) };
+            Ok(Capture::shared(|| serde_json::to_writer_pretty(writer, &snapshot))?)
+        } // fn ..
+
+        /// Replaces the registers, macros and dice of the context with written ones, keeping everything else.
+        pub fn read_state(&mut self, reader: impl Read) -> io::Result<()> {
+            let mut deserializer = serde_json::Deserializer::from_reader(reader);
+            deserializer.disable_recursion_limit();
+            let snapshot = Capture::shared(|| Snapshot::deserialize(&mut deserializer))?;
+            deserializer.end()?;
+
+            self.registers = snapshot.registers.into_iter().map(|(name, value)| (name, value.into_owned())).collect();
+            self.macros    = snapshot.macros.into_iter().map(|(name, value)| (name, value.into_owned())).collect();
+            self.rng       = snapshot.rng.into_owned();
+            Ok(())
+        } // fn ..
+
+        /// Saves the registers, macros and dice of the context to a file.
+        pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+            let mut writer = BufWriter::new(File::create(path)?);
+            self.write_state(&mut writer)?;
+            writer.flush()
+        } // fn ..
+
+        /// Restores the registers, macros and dice of the context from a file.
+        pub fn restore(&mut self, path: impl AsRef<Path>) -> io::Result<()> { self.read_state(BufReader::new(File::open(path)?)) }
+
+        /// Enters a procedure call, failing if it goes deeper than allowed.
+        pub(crate) fn enter_call(&mut self) -> Result<(), InterpreterErr> {
+            if self.call_depth < self.limits.call_depth { self.call_depth += 1usize; Ok(()) }
+            else { Err(InterpreterErr::CallDepthExceeded(self.limits.call_depth)) }
+        } // fn ..
+
+        /// Leaves a procedure call.
+        pub(crate) fn exit_call(&mut self) { self.call_depth = self.call_depth.saturating_sub(1usize); }
+
+        /// Scans a nested expression or block with a given function, failing if it goes deeper than allowed.
+        pub(crate) fn nested<T>(&mut self, scan: impl FnOnce(&mut Self) -> Result<T, InterpreterErr>) -> Result<T, InterpreterErr> {
+            if self.nesting >= self.limits.nesting { return Err(InterpreterErr::NestingExceeded(self.limits.nesting)) }
+            self.nesting += 1usize;
+            let output = scan(self);
+            self.nesting -= 1usize;
+            output
+        } // fn ..
+
+        /// Resets the resources consumed so far, starting a new run.
+        pub fn reset_usage(&mut self) { self.usage = Usage::default(); self.tally = Tally::default(); }
+
+        /// Replaces the output the told values go to.
+        pub fn set_output(&mut self, output: impl Output + 'static) { self.output = Arc::new(output); }
+
+        /// Sends a told value to the output.
+        pub(crate) fn tell(&self, event: TellEvent) { self.output.tell(event); }
+
+        /// Replaces the input the answers come from.
+        pub fn set_input(&mut self, input: impl Input + 'static) { self.input = Arc::new(input); }
+
+        /// Asks the input for a value of a given type until it gives a valid one, failing once it can't answer anymore or rejected too many answers.
+        pub(crate) fn ask(&self, id: &PrimitiveId, prompt: &str, range: Option<(i16, i16)>) -> Result<Primitive, InterpreterErr> {
+            let mut rejected = 0usize;
+            loop {
+                let answer = self.input.ask(prompt).ok_or_else(|| InterpreterErr::NoAnswer(String::from(prompt)))?;
+                match read_answer(id, &answer, range) {
+                    Ok(value)   => break Ok(value),
+                    Err(reason) => self.input.reject(&answer, &reason),
+                } // match ..
+
+                rejected += 1usize;
+                if rejected > self.limits.retries { break Err(InterpreterErr::RetriesExceeded(self.limits.retries)) }
+                self.check_time()?;
+            } // loop ..
+        } // fn ..
+
+        /// Starts recording the thrown dice, returning where the recording starts.
+        pub(crate) fn open_tally(&mut self) -> usize { self.tally.depth += 1usize; self.tally.dice.len() }
+
+        /// Stops recording the thrown dice, returning the dice thrown since a given start.
+        pub(crate) fn close_tally(&mut self, from: usize) -> Vec<i16> {
+            let dice = self.tally.dice.get(from..).unwrap_or_default().to_vec();
+            self.tally.depth = self.tally.depth.saturating_sub(1usize);
+            if self.tally.depth == 0usize { self.tally.dice.clear(); }
+            dice
+        } // fn ..
+
+        /// Records a thrown die while a told value is being evaluated.
+        pub(crate) fn tally_die(&mut self, die: i16) { if self.tally.depth > 0usize { self.tally.dice.push(die); } }
+
+        /// Accounts for an executed statement, failing if the statement budget or the run time is exhausted.
+        pub(crate) fn step(&mut self) -> Result<(), InterpreterErr> {
+            self.usage.statements += 1usize;
+            if self.usage.statements > self.limits.statements { return Err(InterpreterErr::StatementsExceeded(self.limits.statements)) }
+            self.check_time()
+        } // fn ..
+
+        /// Accounts for a loop iteration, failing if the iteration budget or the run time is exhausted.
+        pub(crate) fn iterate(&mut self) -> Result<(), InterpreterErr> {
+            self.usage.iterations += 1usize;
+            if self.usage.iterations > self.limits.iterations { return Err(InterpreterErr::IterationsExceeded(self.limits.iterations)) }
+            self.check_time()
+        } // fn ..
+
+        /// Fails if the run time is exhausted.
+        fn check_time(&self) -> Result<(), InterpreterErr> {
+            match self.limits.timeout {
+                Some(timeout) if self.usage.started.elapsed() > timeout => Err(InterpreterErr::TimedOut(timeout)),
+                _                                                       => Ok(()),
+            } // match ..
+        } // fn ..
+
+        /// Accounts for `n` thrown dice, failing if the dice budget is exhausted.
+        pub(crate) fn throw_dice(&mut self, n: i16) -> Result<(), InterpreterErr> {
+            self.usage.dice = self.usage.dice.saturating_add(n.max(0i16) as usize);
+            if self.usage.dice > self.limits.dice { Err(InterpreterErr::DiceExceeded(self.limits.dice)) } else { Ok(()) }
+        } // fn ..
+
+        /// Fails if allocating `n` more registers would exceed the register budget.
+        pub(crate) fn check_registers(&self, n: usize) -> Result<(), InterpreterErr> {
+            if self.registers.len().saturating_add(n) > self.limits.registers { Err(InterpreterErr::RegistersExceeded(self.limits.registers)) } else { Ok(()) }
+        } // fn ..
+
+        /// Returns every allocated register.
+        pub fn registers(&self) -> &HashMap<Symbol, Primitive> { &self.registers }
+
+        /// Returns the value of the register with a given name.
+        pub fn get_reg(&self, name: &str) -> Option<&Primitive> { self.registers.get(&Symbol::lookup(name)?) }
+
+        /// Returns the value of the register with a given name, converted to a Rust type.
+        pub fn read_reg<T: TryFrom<Primitive, Error = InterpreterErr>>(&self, name: &str) -> Result<T, InterpreterErr> {
+            match Symbol::lookup(name) {
+                Some(name) => self.reg(&name).cloned().and_then(T::try_from),
+                None       => Err(InterpreterErr::UninitReg(Symbol::from(name))),
+            } // match ..
+        } // fn ..
+
+        /// Allocates or replaces the register with a given name, failing if it exceeds the register budget.
+        pub fn set_reg(&mut self, name: &str, value: impl Into<Primitive>) -> Result<(), InterpreterErr> { self.new_reg(&Symbol::from(name), value.into()) }
+
+        /// Desallocates the register with a given name, returning its last value.
+        pub fn remove_reg(&mut self, name: &str) -> Option<Primitive> { self.registers.remove(&Symbol::lookup(name)?) }
+
+        /// Returns the name of every allocated register along with a printout of its value, sorted by name.
+        pub fn describe_registers(&self) -> Vec<(Symbol, String)> {
+            let mut registers = self.registers.iter().map(|(name, value)| (*name, format!("{:?}", value))).collect::<Vec<_>>();
+            registers.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            registers
+        } // fn ..
+
+        /// Returns the name of every allocated register, sorted by name.
+        pub fn register_names(&self) -> Vec<Symbol> {
+            let mut names = self.registers.keys().copied().collect::<Vec<_>>();
+            names.sort_by_key(|name| name.as_str());
+            names
+        } // fn ..
+
+        /// Drops every token left unscanned, so that a failed input isn't scanned again.
+        pub fn discard(&mut self) { self.tokens.truncate(self.cursor); self.spans.truncate(self.cursor); }
 
         /// Moves the context to the next token.
-        pub(crate) fn next(&mut self) { self.next_tokens.remove(0usize); self.token_index += 1usize; }
+        pub(crate) fn next(&mut self) { self.cursor = (self.cursor + 1usize).min(self.tokens.len()); }
         
         /// Peeks the current token.
-        pub(crate) fn peek(&self) -> Option<&WsToken> { self.next_tokens.first() }
+        pub(crate) fn peek(&self) -> Option<&WsToken> { self.tokens.get(self.cursor) }
+
+        /// Peeks the token lying `n` tokens ahead of the current one.
+        pub(crate) fn peek_nth(&self, n: usize) -> Option<&WsToken> { self.tokens.get(self.cursor + n) }
+
+        /// Returns the span of the source lying between a given token index and the current one, within the source it was pushed with.
+        pub(crate) fn span(&self, from: usize) -> Span {
+            match (self.spans.get(from), self.spans.get(self.cursor.saturating_sub(1usize))) {
+                (Some(first), Some(last)) => Span { end: last.end.max(first.start), ..*first },
+                _                         => Span::default(),
+            } // match ..
+        } // fn ..
+
+        /// Moves the context back to a previously peeked token index, allowing the parser to backtrack.
+        pub(crate) fn rewind(&mut self, token_index: usize) { self.cursor = token_index.min(self.tokens.len()); }
+
+        /// Returns the macro defined with a given name, the ones defined by the statements being scanned coming first.
+        pub(crate) fn find_macro(&self, name: &Symbol) -> Option<&Macro> {
+            self.scanned.iter().rev()
+                .find_map(|(scanned, value)| (scanned == name).then_some(value))
+                .or_else(|| self.macros.get(name))
+        } // fn ..
+
+        /// Defines a macro for the rest of the block being scanned, until the statement defining it runs.
+        pub(crate) fn scan_macro(&mut self, name: &Symbol, value: Macro) { self.scanned.push((*name, value)); }
+
+        /// Scans a block with a given function, forgetting the macros it defines once it is scanned.
+        pub(crate) fn macro_scope<T>(&mut self, scan: impl FnOnce(&mut Self) -> T) -> T {
+            let scanned = self.scanned.len();
+            let output  = scan(self);
+            self.scanned.truncate(scanned);
+            output
+        } // fn ..
+
+        /// Defines a macro, replacing any previous one with the same name.
+        pub(crate) fn new_macro(&mut self, name: &Symbol, value: Macro) { self.macros.insert(*name, value); }
+
+        /// Restores a macro to a previously saved definition, or forgets it.
+        pub(crate) fn restore_macro(&mut self, name: &Symbol, value: Option<Macro>) {
+            match value {
+                Some(value) => { self.macros.insert(*name, value); },
+                None        => { self.macros.remove(name); },
+            } // match ..
+        } // fn ..
+
+        /// Registers a Rust function that scripts call like a procedure, as in `integer [name](args)`, replacing any previous one with the same name.
+        pub fn new_host(
+            &mut self,
+            name:   &str,
+            params: &[PrimitiveId],
+            output: PrimitiveId,
+            func:   impl Fn(Vec<Primitive>) -> Result<Primitive, InterpreterErr> + Send + Sync + 'static,
+        ) { self.hosts.insert(Symbol::from(name), Host::new(params, output, func)); }
+
+        /// Returns every registered host function.
+        pub(crate) fn hosts(&self) -> &HashMap<Symbol, Host> { &self.hosts }
+
+        /// Returns the host function with a given name, unless a register shadows it.
+        pub(crate) fn find_host(&self, name: &Symbol) -> Option<Host> {
+            if self.registers.contains_key(name) { None } else { self.hosts.get(name).cloned() }
+        } // fn ..
 
         /// Returns the value stored in a given register.
         pub(crate) fn reg(
             &self,
-            name: &FixedStr<L16>,
+            name: &Symbol,
         ) -> Result<&Primitive, InterpreterErr> {
             match self.registers.get(name) {
                 Some(value) => Ok(value),
-                None        => Err(InterpreterErr::UninitReg(name.clone())),
+                None        => Err(InterpreterErr::UninitReg(*name)),
             } // match ..
         } // fn ..
         
@@ -119,11 +547,11 @@
         /// Returns the mutable value stored in a given register.
         pub(crate) fn reg_mut(
             &mut self,
-            name: &FixedStr<L16>,
+            name: &Symbol,
         ) -> Result<&mut Primitive, InterpreterErr> {
             match self.registers.get_mut(name) {
                 Some(value) => Ok(value),
-                None        => Err(InterpreterErr::UninitReg(name.clone())),
+                None        => Err(InterpreterErr::UninitReg(*name)),
             } // match ..
         } // fn ..
 
@@ -131,33 +559,224 @@
         /// Allocates a new register.
         pub(crate) fn new_reg(
             &mut self,
-            id:    &FixedStr<L16>,
+            id:    &Symbol,
             value: Primitive,
-        ) { self.registers.insert(id.clone(), value); }
+        ) -> Result<(), InterpreterErr> {
+            if !self.registers.contains_key(id) { self.check_registers(1usize)?; }
+            self.registers.insert(*id, value);
+            Ok(())
+        } // fn ..
+
+
+        /// Restores a register to a previously saved value, regardless of the register budget.
+        pub(crate) fn restore_reg(
+            &mut self,
+            id:    &Symbol,
+            value: Option<Primitive>,
+        ) {
+            match value {
+                Some(value) => { self.registers.insert(*id, value); },
+                None        => { self.registers.remove(id); },
+            } // match ..
+        } // fn ..
 
 
         /// Desallocates an old register.
         pub(crate) fn del_reg(
             &mut self,
-            id: &FixedStr<L16>,
+            id: &Symbol,
         ) { self.registers.remove(id); }
 
         
         /// Returns an eventual reference.
-        pub(crate) fn next_ref(&mut self) -> Result<FixedStr<L16>, InterpreterErr> {
-            if WsToken::LeftBracket == *self.peek().unwrap() {
+        pub(crate) fn next_ref(&mut self) -> Result<Symbol, InterpreterErr> {
+            match (self.peek(), self.peek_nth(1usize), self.peek_nth(2usize)) {
+                (Some(WsToken::LeftBracket), Some(WsToken::Ident(name)), Some(WsToken::RightBracket)) => {
 
-                self.next();
-                if let Some(WsToken::Ident(name)) = self.peek() {
-
-                    let name = name.clone();
-                    self.next();
-                    self.next();
+                    let name = *name;
+                    self.cursor += 3usize;
                     
                     Ok(name)
 
-                } else { Err(InterpreterErr::ExpectedARef(self.token_index)) }
-            } else { Err(InterpreterErr::ExpectedARef(self.token_index)) }
+                }, // => ..
+                _ => Err(InterpreterErr::ExpectedARef(self.cursor)),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns an eventual reference along with the path of record fields following it, as in `[hero.stats.str]`.
+        pub(crate) fn next_path(&mut self) -> Result<(Symbol, Vec<Symbol>), InterpreterErr> {
+
+            let start = self.cursor;
+            let name  = match (self.peek(), self.peek_nth(1usize)) {
+                (Some(WsToken::LeftBracket), Some(WsToken::Ident(name))) => { let name = *name; self.cursor += 2usize; name },
+                _                                                        => return Err(InterpreterErr::ExpectedARef(start)),
+            }; // let ..
+
+            let mut path = Vec::default();
+            while self.peek() == Some(&WsToken::Dot) {
+                match self.peek_nth(1usize) {
+                    Some(WsToken::Ident(field)) => { path.push(*field); self.cursor += 2usize; },
+                    _                           => return Err(InterpreterErr::ExpectedARef(start)),
+                } // match ..
+            } // while ..
+
+            match self.peek() {
+                Some(WsToken::RightBracket) => { self.next(); Ok((name, path)) },
+                _                           => Err(InterpreterErr::ExpectedARef(start)),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns an eventual list of parameters.
+        pub(crate) fn next_params(&mut self) -> Result<Vec<Symbol>, InterpreterErr> {
+
+            let mut params = Vec::default();
+            if self.peek() == Some(&WsToken::LeftParen) {
+
+                self.next();
+                while self.peek() != Some(&WsToken::RightParen) {
+                    params.push(self.next_ref()?);
+                    match self.peek() {
+                        Some(WsToken::Comma)      => self.next(),
+                        Some(WsToken::RightParen) => (),
+                        Some(token)               => return Err(InterpreterErr::WrongToken(self.cursor, token.clone())),
+                        None                      => return Err(InterpreterErr::WrongEof(self.cursor)),
+                    } // match ..
+                } // while ..
+
+                self.next();
+            } // if ..
+
+            Ok(params)
+        } // fn ..
+
+
+        /// Returns an eventual list of arguments.
+        pub(crate) fn next_args(&mut self) -> Result<Option<Vec<Expr>>, InterpreterErr> {
+
+            if self.peek() != Some(&WsToken::LeftParen) { return Ok(None) }
+
+            let mut args = Vec::default();
+            self.next();
+
+            if self.peek() == Some(&WsToken::RightParen) { self.next(); return Ok(Some(args)) }
+            loop {
+                args.push(Expr::scan_until(self, None, &[WsToken::Comma, WsToken::RightParen])?);
+                let end = self.peek().cloned();
+                self.next();
+
+                if end == Some(WsToken::RightParen) { break Ok(Some(args)) }
+            } // loop ..
+        } // fn ..
+
+
+        /// Returns the fields of a record literal, each written as `[name] <- expression`.
+        pub(crate) fn next_fields(&mut self) -> Result<Vec<(Symbol, Expr)>, InterpreterErr> {
+
+            let mut fields = Vec::<(Symbol, Expr)>::default();
+            self.next_token(WsToken::LeftParen)?;
+
+            while self.peek() != Some(&WsToken::RightParen) {
+                let index = self.cursor;
+                let name  = self.next_ref()?;
+                if fields.iter().any(|(field, _)| *field == name) { return Err(InterpreterErr::DuplicateField(index, name)) }
+
+                self.next_token(WsToken::InArrow)?;
+                fields.push((name, Expr::scan_until(self, None, &[WsToken::Comma, WsToken::RightParen])?));
+                if self.peek() == Some(&WsToken::Comma) { self.next(); }
+            } // while ..
+
+            self.next();
+            Ok(fields)
+        } // fn ..
+
+
+        /// Returns an eventual index, written as a single argument.
+        pub(crate) fn next_index(&mut self) -> Result<Option<Expr>, InterpreterErr> {
+            match self.next_args()? {
+                Some(mut args) if args.len() == 1usize => Ok(args.pop()),
+                Some(args)                             => Err(InterpreterErr::WrongArity(1usize, args.len())),
+                None                                   => Ok(None),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns an eventual integer literal, which may be negative.
+        pub(crate) fn next_int(&mut self) -> Result<i16, InterpreterErr> {
+
+            let sign = if self.peek() == Some(&WsToken::Minus) { self.next(); -1i16 } else { 1i16 };
+            match self.peek() {
+                Some(WsToken::DigitLit(int)) => { let int = *int; self.next(); Ok(sign * int) },
+                Some(token)                  => Err(InterpreterErr::WrongToken(self.cursor, token.clone())),
+                None                         => Err(InterpreterErr::WrongEof(self.cursor)),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns an eventual table row, covering either a single roll or a range of rolls.
+        pub(crate) fn next_row(&mut self) -> Result<(i16, i16, Decl), InterpreterErr> {
+
+            let from = self.next_int()?;
+            let to   = if self.peek() == Some(&WsToken::Range) { self.next(); self.next_int()? } else { from };
+            let decl = self.next_decl(WsToken::OutArrow)?;
+
+            Ok((from.min(to), from.max(to), decl))
+        } // fn ..
+
+
+        /// Returns an eventual question, written as `ask integer 'prompt'`, the integers asked for being eventually bounded as in `ask integer 'prompt' in 1 .. 6`.
+        pub(crate) fn next_ask(&mut self) -> Result<Val, InterpreterErr> {
+
+            self.next_token(WsToken::Ask)?;
+            let id = match self.peek() {
+                Some(token @ (WsToken::Int | WsToken::Bool | WsToken::Str)) => PrimitiveId::try_from(token)?,
+                Some(token)                                                 => return Err(InterpreterErr::WrongToken(self.cursor, token.clone())),
+                None                                                        => return Err(InterpreterErr::WrongEof(self.cursor)),
+            }; // let ..
+            self.next();
+
+            let prompt = match self.peek() {
+                Some(WsToken::StrLit(prompt)) => { let prompt = prompt.clone(); self.next(); prompt },
+                Some(token)                   => return Err(InterpreterErr::WrongToken(self.cursor, token.clone())),
+                None                          => return Err(InterpreterErr::WrongEof(self.cursor)),
+            }; // let ..
+
+            let range = if id == PrimitiveId::Int && self.peek() == Some(&WsToken::In) {
+                self.next();
+                let from = self.next_int()?;
+                self.next_token(WsToken::Range)?;
+                let to   = self.next_int()?;
+                Some((from.min(to), from.max(to)))
+            } else { None };
+
+            Ok(Val::Ask(id, prompt, range))
+        } // fn ..
+
+
+        /// Returns the bounds of a counting loop, written as `| from .. to by step |` or `| from ..< to by step |`.
+        pub(crate) fn next_bounds(&mut self) -> Result<Bounds, InterpreterErr> {
+
+            self.next_token(WsToken::Pipe)?;
+            let from      = Expr::scan_until(self, None, &[WsToken::Range, WsToken::OpenRange])?;
+            let inclusive = self.peek() == Some(&WsToken::Range);
+            self.next();
+
+            let to   = Expr::scan_until(self, None, &[WsToken::By, WsToken::Pipe])?;
+            let step = if self.peek() == Some(&WsToken::By) { self.next(); Some(Expr::scan_until(self, None, &[WsToken::Pipe])?) } else { None };
+            self.next();
+
+            Ok(Bounds { from, to, step, inclusive })
+        } // fn ..
+
+
+        /// Consumes an expected token.
+        pub(crate) fn next_token(&mut self, token: WsToken) -> Result<(), InterpreterErr> {
+            match self.peek() {
+                Some(found) if *found == token => { self.next(); Ok(()) },
+                Some(found)                    => Err(InterpreterErr::WrongToken(self.cursor, found.clone())),
+                None                           => Err(InterpreterErr::WrongEof(self.cursor)),
+            } // match ..
         } // fn ..
 
 
@@ -173,7 +792,26 @@
                 self.next();
                 Ok(Expr::scan(self, None, end_token)?)
 
-            } else { Err(InterpreterErr::ExpectedAnExpr(self.token_index)) }
+            } else { Err(InterpreterErr::ExpectedAnExpr(self.cursor)) }
+        } // fn ..
+
+
+        /// Returns the bound of a statistical assertion, read as a decimal when written as one.
+        pub(crate) fn next_bound(&mut self, end_token: WsToken) -> Result<Bound, InterpreterErr> {
+
+            let sign = usize::from(self.peek() == Some(&WsToken::Minus));
+            let decimal = match (self.peek_nth(sign), self.peek_nth(sign + 1usize), self.peek_nth(sign + 2usize), self.peek_nth(sign + 3usize)) {
+                (Some(WsToken::DigitLit(whole)), Some(WsToken::Dot), Some(WsToken::DigitLit(fraction)), Some(end)) if *end == end_token => {
+                    let digits = self.spans.get(self.cursor + sign + 2usize).map_or(0usize, |span| span.end - span.start);
+                    Some(format!("{}{}.{:0>digits$}", if sign == 1usize { "-" } else { "" }, whole, fraction))
+                }, // => ..
+                _ => None,
+            }; // let ..
+
+            match decimal {
+                Some(decimal) => { (0usize..sign + 4usize).for_each(|_| { self.next(); }); Ok(Bound::Decimal(decimal)) },
+                None          => Ok(Bound::Expr(Expr::scan(self, None, end_token)?)),
+            } // match ..
         } // fn ..
 
 
@@ -189,6 +827,174 @@
                 self.next();
                 Ok(Decl::Closure(Stm::scan(self, Vec::default(), WsToken::Eos)?))
 
-            }  else { Err(InterpreterErr::ExpectedADecl(self.token_index)) }
+            }  else { Err(InterpreterErr::ExpectedADecl(self.cursor)) }
         } // fn ..
     } // impl ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use std::iter;
+        use crate::{SyntaxContext, SyntaxElement, Stm, Decl, WsToken, Program, Limits, InterpreterErr, ScriptedInput, Primitive, PrimitiveId, Symbol};
+
+        /// Scans a script in a fresh context with the given limits.
+        fn scan(src: &str, limits: Limits) -> (SyntaxContext, Vec<Stm>) {
+            let mut context = SyntaxContext::default();
+            context.set_limits(limits);
+            context.push(src).unwrap();
+            let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+            (context, stms)
+        } // fn ..
+
+        #[test]
+        fn cursor_walks_the_token_buffer() {
+            let mut context = SyntaxContext::default();
+            context.push("let [x] <- 1;").unwrap();
+
+            assert_eq!(context.peek(), Some(&WsToken::Let));
+            assert_eq!(context.peek_nth(1usize), Some(&WsToken::LeftBracket));
+            context.next();
+            let index = context.token_index();
+            assert_eq!(context.next_ref().unwrap(), crate::Symbol::from("x"));
+            assert_eq!(context.peek(), Some(&WsToken::InArrow));
+
+            context.rewind(index);
+            assert_eq!(context.peek(), Some(&WsToken::LeftBracket));
+            context.rewind(usize::MAX);
+            assert_eq!(context.peek(), None);
+            context.next();
+            assert_eq!(context.token_index(), context.tokens.len());
+
+            context.rewind(0usize);
+            let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+            assert_eq!(stms.len(), 1usize);
+            context.discard();
+            context.push("let [y] <- 2;").unwrap();
+            assert_eq!(Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap().len(), 1usize);
+        } // fn ..
+
+        #[test]
+        fn reading_unknown_names_does_not_intern_them() {
+            let (mut context, stms) = scan("let [hero] <- record ([str] <- 12);", Limits::default());
+            Program::compile(&context, &stms).run(&mut context).unwrap();
+
+            let Some(Primitive::Record(hero)) = context.get_reg("hero").cloned() else { panic!("expected a record") };
+            assert_eq!(hero.get("str"), Some(&Primitive::Int(12i16)));
+            assert!(hero.get("unknown field name").is_none());
+            assert!(context.get_reg("unknown register name").is_none());
+            assert!(context.remove_reg("unknown register name").is_none());
+
+            assert!(Symbol::lookup("unknown field name").is_none());
+            assert!(Symbol::lookup("unknown register name").is_none());
+            assert_eq!(Symbol::lookup("hero"), Some(Symbol::from("hero")));
+        } // fn ..
+
+        #[test]
+        fn registers_are_read_and_written_by_name() {
+            let (mut context, stms) = scan("let [hp] <- 12; let [names] <- list ('ann', 'bob');", Limits { registers: 4usize, ..Limits::default() });
+            Program::compile(&context, &stms).run(&mut context).unwrap();
+
+            assert_eq!(context.read_reg::<i16>("hp").ok(), Some(12i16));
+            assert_eq!(context.read_reg::<Vec<String>>("names").ok(), Some(vec![String::from("ann"), String::from("bob")]));
+            assert!(matches!(context.read_reg::<bool>("hp"), Err(InterpreterErr::MismatchedValue(PrimitiveId::Bool, PrimitiveId::Int))));
+            assert!(matches!(context.read_reg::<i16>("mp"), Err(InterpreterErr::UninitReg(name)) if name.as_str() == "mp"));
+
+            context.set_reg("hp", 3i16).unwrap();
+            context.set_reg("name", "ann").unwrap();
+            context.set_reg("alive", true).unwrap();
+            assert_eq!(context.read_reg::<i16>("hp").ok(), Some(3i16));
+            assert_eq!(context.read_reg::<String>("name").ok(), Some(String::from("ann")));
+            assert!(matches!(context.set_reg("mp", 1i16), Err(InterpreterErr::RegistersExceeded(4usize))));
+            assert_eq!(context.register_names().iter().map(Symbol::as_str).collect::<Vec<_>>(), vec!["alive", "hp", "name", "names"]);
+
+            assert_eq!(context.remove_reg("hp"), Some(Primitive::Int(3i16)));
+            assert_eq!(context.remove_reg("hp"), None);
+            context.set_reg("mp", 1i16).unwrap();
+            assert_eq!(context.register_names().iter().map(Symbol::as_str).collect::<Vec<_>>(), vec!["alive", "mp", "name", "names"]);
+        } // fn ..
+
+        #[test]
+        fn limits_stop_both_engines() {
+            let cases = [
+                ("define [f]([n]): out -> integer [f](integer [n] + 1); >>>> let [x] <- integer [f](0);", Limits { call_depth: 16usize, ..Limits::default() }, "call depth"),
+                ("let [i] <- 0; while | integer [i] < 100 |: set [i] <- integer [i] + 1; >>>>",          Limits { statements: 50usize,  ..Limits::default() }, "statements"),
+                ("let [x] <- 0; for [i] in | 1..100 |: set [x] <- integer [x] + 1d6; >>>>",               Limits { dice:       50usize,  ..Limits::default() }, "dice"),
+                ("let [x] <- 0; for [i] in | 1..100 |: set [x] <- integer [x] + 1; >>>>",                 Limits { iterations: 50usize,  ..Limits::default() }, "iterations"),
+                ("let [a] <- 1; let [b] <- 2; let [c] <- 3; let [e] <- 4;",                              Limits { registers:  3usize,   ..Limits::default() }, "registers"),
+            ]; // let ..
+
+            for (src, limits, name) in cases {
+                let (mut context, stms) = scan(src, limits);
+                let mut tree            = context.clone();
+
+                let vm = Program::compile(&context, &stms).run(&mut context).map(|_| ());
+                tree.reset_usage();
+                let tr = Stm::run(&mut tree, &Decl::Closure(stms)).map(|_| ());
+
+                for (engine, result) in [("VM", vm), ("tree interpreter", tr)] {
+                    assert!(matches!((name, result),
+                        ("call depth", Err(InterpreterErr::CallDepthExceeded(16usize)))
+                      | ("statements", Err(InterpreterErr::StatementsExceeded(50usize)))
+                      | ("dice",       Err(InterpreterErr::DiceExceeded(50usize)))
+                      | ("iterations", Err(InterpreterErr::IterationsExceeded(50usize)))
+                      | ("registers",  Err(InterpreterErr::RegistersExceeded(3usize)))
+                    ), "the {} limit on the {}", name, engine);
+                } // for ..
+            } // for ..
+
+            let mut context = SyntaxContext::default();
+            context.set_limits(Limits { nesting: 4usize, ..Limits::default() });
+            context.push("let [x] <- ((((1))));").unwrap();
+            assert!(matches!(Stm::scan(&mut context, Vec::default(), WsToken::Eof), Err(InterpreterErr::NestingExceeded(4usize))));
+        } // fn ..
+
+        #[test]
+        fn untrusted_limits_bound_every_budget() {
+            let limits = Limits::untrusted();
+            assert!(limits.timeout.is_some());
+            for budget in [limits.call_depth, limits.nesting, limits.statements, limits.dice, limits.iterations, limits.registers, limits.retries] {
+                assert!(budget < usize::MAX);
+            } // for ..
+
+            for src in ["while | 1 = 1 |: >>>>", "define [f]: out -> integer [f]; >>>> let [x] <- integer [f];", "let [x] <- 30000d6;"] {
+                let (mut context, stms) = scan(src, limits.clone());
+                assert!(Program::compile(&context, &stms).run(&mut context).is_err(), "`{}`", src);
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn timeout_stops_loops_without_statements() {
+            let limits = Limits { timeout: Some(Duration::from_millis(20u64)), ..Limits::default() };
+
+            for src in ["while | 1 = 1 |: >>>>", "let [i] <- 0; while | integer [i] + 1d6 > 0 |: >>>>"] {
+                let (mut context, stms) = scan(src, limits.clone());
+                let mut tree            = context.clone();
+
+                assert!(matches!(Program::compile(&context, &stms).run(&mut context), Err(InterpreterErr::TimedOut(..))), "`{}` on the VM", src);
+                tree.reset_usage();
+                assert!(matches!(Stm::run(&mut tree, &Decl::Closure(stms)), Err(InterpreterErr::TimedOut(..))), "`{}` on the tree interpreter", src);
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn rejected_answers_are_capped() {
+            let limits              = Limits { retries: 3usize, ..Limits::default() };
+            let (mut context, stms) = scan("let [n] <- ask integer 'How many?' in 1 .. 6;", limits);
+            let mut tree            = context.clone();
+
+            let input = ScriptedInput::new(iter::repeat_n("seven", 100usize));
+            context.set_input(input.clone());
+            assert!(matches!(Program::compile(&context, &stms).run(&mut context), Err(InterpreterErr::RetriesExceeded(3usize))));
+            assert_eq!(input.rejected().len(), 4usize);
+
+            let input = ScriptedInput::new(iter::repeat_n("seven", 100usize));
+            tree.set_input(input.clone());
+            assert!(matches!(Stm::run(&mut tree, &Decl::Closure(stms)), Err(InterpreterErr::RetriesExceeded(3usize))));
+            assert_eq!(input.rejected().len(), 4usize);
+        } // fn ..
+    } // mod ..
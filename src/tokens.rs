@@ -4,11 +4,15 @@
 
     use std::{fmt, ops::Range};
     use std::fmt::Display;
+    use std::cell::RefCell;
+    use std::mem;
     use rusty_toolkit::{
         Token, ReadToken,
-        LexingErr,
-        FixedStr, L16, L32
+        Lexer, LexingErr,
     }; // use ..
+    use serde::{Serialize, Deserialize};
+
+    use crate::Symbol;
 
 
 //#######################
@@ -17,30 +21,115 @@
 
     #[derive(Clone, Debug, PartialEq, Eq)]
     pub enum WsToken {
-        Let, Set, If, While, Choose, Out, Tell, Def,
+        Let, Set, If, While, Choose, Out, Tell, Def, Assert,
+        For, Each, In, By, Append, Remove, Length,
+        Mean, Never, Within, Macro, Ask,
 
-        Ident(FixedStr<L16>), DigitLit(i16), StrLit(FixedStr<L32>),
+        Ident(Symbol), DigitLit(i16), StrLit(String),
 
-        Int, Bool, Str, Deck, Void,
+        Int, Bool, Str, Deck, Void, Proc, List, Table, Record,
 
         Plus, Minus, Star, Slash, Dice,
         Eq, Ne, Gt, Ge, Lt, Le,
         InArrow, OutArrow,
         LeftParen,   RightParen,
         LeftBracket, RightBracket,
-        Colon, SemiColon, Qmark,
+        Colon, SemiColon, Comma, Qmark,
         Pipe,
         Tide,
-        Ellipsis,
+        Dot, Range, OpenRange, Ellipsis,
 
         Eos, Eof,
     } // enum WsToken
 
 
+    /// Where a piece of source lies, as byte offsets along with the line and column it starts at, both counted from one.
+    #[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Span {
+        pub start:  usize,
+        pub end:    usize,
+        pub line:   usize,
+        pub column: usize,
+    } // struct ..
+
+
+    thread_local! {
+        /// The source range of every token read so far by the lexer running on this thread.
+        static READ: RefCell<Vec<Range<usize>>> = RefCell::default();
+    } // thread_local ..
+
+
 //###############################
 // I M P L E M E N T A T I O N S
 //###############################
 
+    impl WsToken {
+        /// The words read as keywords rather than identifiers, along with their tokens.
+        pub const KEYWORDS: &'static [(&'static str, WsToken)] = &[
+            ("let",     WsToken::Let),
+            ("set",     WsToken::Set),
+            ("if",      WsToken::If),
+            ("while",   WsToken::While),
+            ("choose",  WsToken::Choose),
+            ("out",     WsToken::Out),
+            ("tell",    WsToken::Tell),
+            ("assert",  WsToken::Assert),
+            ("define",  WsToken::Def),
+            ("for",     WsToken::For),
+            ("each",    WsToken::Each),
+            ("in",      WsToken::In),
+            ("by",      WsToken::By),
+            ("append",  WsToken::Append),
+            ("remove",  WsToken::Remove),
+            ("length",  WsToken::Length),
+            ("mean",    WsToken::Mean),
+            ("never",   WsToken::Never),
+            ("within",  WsToken::Within),
+            ("macro",   WsToken::Macro),
+            ("ask",     WsToken::Ask),
+
+            ("integer", WsToken::Int),
+            ("boolean", WsToken::Bool),
+            ("void",    WsToken::Void),
+            ("text",    WsToken::Str),
+            ("deck",    WsToken::Deck),
+            ("proc",    WsToken::Proc),
+            ("list",    WsToken::List),
+            ("table",   WsToken::Table),
+            ("record",  WsToken::Record),
+        ]; // const ..
+
+
+        /// Lexes a source into tokens along with their spans, walking the source once to count lines and columns.
+        pub(crate) fn lex(src: &str) -> Result<(Vec<WsToken>, Vec<Span>), LexingErr> {
+
+            READ.with(|read| read.borrow_mut().clear());
+            let tokens = Lexer::run::<WsToken>(src)?;
+            let mut read = READ.with(|read| mem::take(&mut *read.borrow_mut()));
+            read.push(src.len()..src.len());
+
+            debug_assert_eq!(read.len(), tokens.len(), "every token should be read exactly once");
+            if read.len() != tokens.len() { let spans = vec![Span::default(); tokens.len()]; return Ok((tokens, spans)) }
+
+            let (mut at, mut line, mut column) = (0usize, 1usize, 1usize);
+            let spans = read.into_iter().map(|range| {
+                debug_assert!(at <= range.start, "tokens should be read in order");
+                for c in src[at.min(range.start)..range.start].chars() {
+                    if c == '\n' { line += 1usize; column = 1usize; } else { column += 1usize; }
+                } // for ..
+                at = at.max(range.start);
+                Span { start: range.start, end: range.end, line, column }
+            }).collect();
+
+            Ok((tokens, spans))
+        } // fn ..
+
+
+        /// Notes the source range of a token just read.
+        fn read(range: Range<usize>) { READ.with(|read| read.borrow_mut().push(range)) }
+    } // impl ..
+
+
     impl Token for WsToken {
         const EOF:              Self         = WsToken::Eof;
         const COMMENT_KEY:      Option<char> = Some('#');
@@ -49,32 +138,29 @@
     } // impl ..
 
     impl ReadToken for WsToken {
-        fn read_str_lit(input: &str, peek: Range<usize>)   -> Self { WsToken::StrLit(FixedStr::from(&input[peek])) }
-        fn read_ident(input: &str, peek: Range<usize>)     -> Self { WsToken::Ident(FixedStr::from(&input[peek])) }
+        fn read_str_lit(input: &str, peek: Range<usize>)   -> Self { Self::read(peek.start.saturating_sub(1usize)..(peek.end + 1usize).min(input.len())); WsToken::StrLit(String::from(&input[peek])) }
+        fn read_ident(input: &str, peek: Range<usize>)     -> Self { Self::read(peek.clone()); WsToken::Ident(Symbol::from(&input[peek])) }
         fn read_digit_lit(input: &str, peek: Range<usize>) -> Result<Self, LexingErr> {
             match &input[peek.clone()].parse::<i16>() {
-                Ok(x)  => Ok(WsToken::DigitLit(*x)),
+                Ok(x)  => { Self::read(peek); Ok(WsToken::DigitLit(*x)) },
                 Err(_) => Err(LexingErr::NotADigit(peek.start)),
             } // match ..
         } // fn ..
     
         fn read_keyword(input: &str, peek: Range<usize>) -> Result<Self, LexingErr> {
-            match &input[peek.clone()] {
-                "let"    => Ok(WsToken::Let),
-                "set"    => Ok(WsToken::Set),
-                "if"     => Ok(WsToken::If),
-                "while"  => Ok(WsToken::While),
-                "choose" => Ok(WsToken::Choose),
-                "out"    => Ok(WsToken::Out),
-                "tell"   => Ok(WsToken::Tell),
-                "define" => Ok(WsToken::Def),
-
-                "integer" => Ok(WsToken::Int),
-                "boolean" => Ok(WsToken::Bool),
-                "void"    => Ok(WsToken::Void),
-                "text"    => Ok(WsToken::Str),
-                "deck"    => Ok(WsToken::Deck),
+            let token = Self::keyword(input, peek.clone());
+            if token.is_ok() { Self::read(peek) }
+            token
+        } // fn ..
+    } // impl ReadToken ..
+
 
+    impl WsToken {
+        /// Reads a keyword or a symbol.
+        fn keyword(input: &str, peek: Range<usize>) -> Result<Self, LexingErr> {
+            if let Some((_, token)) = Self::KEYWORDS.iter().find(|(word, _)| *word == &input[peek.clone()]) { return Ok(token.clone()) }
+
+            match &input[peek.clone()] {
                 "+" => Ok(WsToken::Plus),
                 "-" => Ok(WsToken::Minus),
                 "*" => Ok(WsToken::Star),
@@ -98,24 +184,69 @@
 
                 ":"   => Ok(WsToken::Colon),
                 ";"   => Ok(WsToken::SemiColon),
+                ","   => Ok(WsToken::Comma),
                 "?"   => Ok(WsToken::Qmark),
                 "~"   => Ok(WsToken::Tide),
                 "|"   => Ok(WsToken::Pipe),
+                "."   => Ok(WsToken::Dot),
+                ".."  => Ok(WsToken::Range),
+                "..<" => Ok(WsToken::OpenRange),
                 "..." => Ok(WsToken::Ellipsis),
 
                 ">>>>" => Ok(WsToken::Eos),
                 ""     => Ok(WsToken::Eof),
                 _      => Err(LexingErr::NotAKeyword(peek.start)),
-        }} // fn ..
-    } // impl ReadToken ..
+            } // match ..
+        } // fn ..
+    } // impl ..
 
 
     impl Display for WsToken {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             write!(f, "{}", match self {
                 WsToken::Ident(ident) => format!("Ident({})", ident.as_ref()),
-                WsToken::StrLit(lit)  => String::from(lit.as_ref()),
+                WsToken::StrLit(lit)  => lit.clone(),
                 _                     => format!("{:?}", self),
             }) // write()
         } // fn ..
     } // impl ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{WsToken, Span};
+        use std::collections::HashSet;
+
+        #[test]
+        fn spans_count_lines_and_characters() {
+            let src = "let [x] <- 1;\n\n  tell -> 'é' + 2;\n>>>>";
+            let (tokens, spans) = WsToken::lex(src).unwrap();
+            assert_eq!(tokens.len(), spans.len());
+
+            let places = spans.iter().map(|span| (span.line, span.column)).collect::<Vec<_>>();
+            assert_eq!(places, vec![(1, 1), (1, 5), (1, 6), (1, 7), (1, 9), (1, 12), (1, 13), (3, 3), (3, 8), (3, 11), (3, 15), (3, 17), (3, 18), (4, 1), (4, 5)]);
+            assert_eq!(&src[spans[9].start..spans[9].end], "'é'");
+            assert_eq!(spans[14], Span { start: src.len(), end: src.len(), line: 4usize, column: 5usize });
+        } // fn ..
+
+        #[test]
+        fn keywords_come_from_one_table() {
+            let words = WsToken::KEYWORDS.iter().map(|(word, _)| *word).collect::<HashSet<&str>>();
+            assert_eq!(words.len(), WsToken::KEYWORDS.len());
+
+            for (word, token) in WsToken::KEYWORDS {
+                let (tokens, _) = WsToken::lex(word).unwrap();
+                assert_eq!(tokens, vec![token.clone(), WsToken::Eof], "`{}`", word);
+            } // for ..
+
+            let readme = include_str!("../README.md");
+            for word in readme.split(|c: char| !c.is_ascii_alphanumeric() && c != '_').filter(|word| word.starts_with(|c: char| c.is_ascii_alphabetic())) {
+                let keyword = WsToken::keyword(word, 0usize..word.len()).is_ok();
+                assert_eq!(keyword, word == "d" || words.contains(word), "`{}`", word);
+            } // for ..
+        } // fn ..
+    } // mod ..
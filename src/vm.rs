@@ -0,0 +1,1643 @@
+//#########################
+// D E P E N D E N C I E S
+//#########################
+
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use crate::{
+        Stm, Decl, Bounds, Bound, Sample, Stats,
+        Expr, Val, Op, CmpTo, Pool, Faces, Macro,
+        SyntaxContext,
+        InterpreterErr,
+        Primitive, PrimitiveId, Proc, Table, Record, Capture,
+        Symbol,
+        TellEvent,
+        Span,
+    }; // use ..
+
+
+//#######################
+// D E F I N I T I O N S
+//#######################
+
+    /// A chain of statements compiled down to bytecode with resolved register slots.
+    #[derive(Debug, Clone)]
+    pub struct Program {
+        code:   Vec<Instr>,
+        end:    usize,
+        names:  Vec<Symbol>,
+        slots:  HashMap<Symbol, usize>,
+        funcs:  Vec<Func>,
+        procs:  HashMap<usize, usize>,
+        tables: Vec<CompiledTable>,
+        consts: Vec<Primitive>,
+    } // struct ..
+
+
+    /// The instruction enumerator.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) enum Instr {
+        Int(i16), Bool(bool), Text(Rc<str>), Void,  Const(usize),
+        Load(usize), Fetch(usize),    Call(usize, usize, PrimitiveId), Cast(PrimitiveId),
+        Ask(PrimitiveId, String, Option<(i16, i16)>),
+        Let(usize),  Set(usize),      Def(usize, usize),               Lambda(usize),
+        Table(usize, usize),          Lookup(usize),
+        List(usize), Store(usize),    Append(usize),                   Remove(usize),
+        Record(Vec<Symbol>),   Field(usize, Vec<Symbol>, PrimitiveId), Update(usize, Vec<Symbol>),
+        Save(usize), Restore(usize),  Items,                           Next(PrimitiveId, usize),
+        Enter(Vec<usize>),            Leave(usize),                    Pop,
+        Count(bool, usize),           Runs, Sample(usize),             Collect,
+        Neg, Len, Add, Sub, Mul, Div,
+        Dice(Option<Cmp>), Pool(Pool), Cmp(Cmp),
+        Jump(usize), JumpUnless(usize),
+        Step(usize), Iter,
+        Tally, Tell(Span, Expr),  Assert(usize, Expr), Mean(usize, Expr), Never(usize, Expr), Macro(Symbol, Macro),
+        Ret, Halt,
+    } // enum ..
+
+
+    /// The comparison enumerator.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) enum Cmp { Eq, Ne, Gt, Ge, Lt, Le }
+
+
+    /// A value living on the stack or in a register slot, lists and records being shared until written to.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Value {
+        Int(i16),
+        Bool(bool),
+        Text(Rc<str>),
+        Proc(Rc<Closure>),
+        List(Rc<Vec<Value>>),
+        Table(usize),
+        Record(Rc<Vec<(Symbol, Value)>>),
+        Void,
+    } // enum ..
+
+
+    /// A compiled procedure body.
+    #[derive(Debug, Clone)]
+    struct Func {
+        entry:    usize,
+        params:   Vec<usize>,
+        captures: Vec<usize>,
+        proc:     Proc,
+    } // struct ..
+
+
+    /// A compiled random table, its dice leading to the body of the matching row.
+    #[derive(Debug, Clone)]
+    struct CompiledTable {
+        entry: usize,
+        rows:  Vec<(i16, i16, usize)>,
+        table: Table,
+    } // struct ..
+
+
+    /// A procedure value created at run time, sharing the cells of its captures with every copy of it.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Closure {
+        func:     usize,
+        captures: Vec<(usize, Capture)>,
+    } // struct ..
+
+
+    /// A pending procedure call, along with the captures to write back to once it returns.
+    struct Frame {
+        ret:   usize,
+        saved: Vec<(usize, Option<Value>)>,
+        cells: Vec<(usize, Capture)>,
+    } // struct ..
+
+
+    /// The state of a running program.
+    struct Machine<'a> {
+        program:  &'a Program,
+        slots:    Vec<Option<Value>>,
+        stack:    Vec<Value>,
+        calls:    Vec<Frame>,
+        scopes:   Vec<Vec<(usize, Option<Value>)>>,
+        tallies:  Vec<usize>,
+        live:     usize,
+    } // struct ..
+
+
+    /// Where an `out` statement leads to, along with the number of scopes open outside of it.
+    enum Exit {
+        Halt,
+        Ret,
+        Yield(usize, Vec<usize>),
+        Skip(usize, usize),
+    } // enum ..
+
+
+    /// A body waiting to be compiled after the main chain of statements.
+    enum Pending {
+        Func(usize),
+        Table(usize),
+    } // enum ..
+
+
+    struct Compiler {
+        code:    Vec<Instr>,
+        slots:   HashMap<Symbol, usize>,
+        names:   Vec<Symbol>,
+        funcs:   Vec<Func>,
+        procs:   HashMap<usize, usize>,
+        tables:  Vec<CompiledTable>,
+        consts:  Vec<Primitive>,
+        pending: Vec<Pending>,
+        depth:   usize,
+        blocks:  usize,
+    } // struct ..
+
+
+//###############################
+// I M P L E M E N T A T I O N S
+//###############################
+
+    impl From<&CmpTo> for Cmp {
+        fn from(value: &CmpTo) -> Self {
+            match value {
+                CmpTo::Eq(..) => Cmp::Eq,
+                CmpTo::Ne(..) => Cmp::Ne,
+                CmpTo::Gt(..) => Cmp::Gt,
+                CmpTo::Ge(..) => Cmp::Ge,
+                CmpTo::Lt(..) => Cmp::Lt,
+                CmpTo::Le(..) => Cmp::Le,
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Value {
+        fn as_int(&self) -> i16 {
+            match self {
+                Value::Int(int)   => *int,
+                Value::Bool(bool) => i16::from(*bool),
+                Value::List(list) => list.len() as i16,
+                Value::Record(rc) => rc.len() as i16,
+                _                 => 0i16,
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the number a bound of a statistical assertion stands for, a decimal being pushed as its text.
+        fn as_real(&self) -> f64 {
+            match self {
+                Value::Text(text) => text.parse::<f64>().unwrap_or_default(),
+                _                 => self.as_int() as f64,
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the faces of a die, a list standing for a custom die.
+        fn faces(&self) -> Faces {
+            match self {
+                Value::List(list) => Faces::List(list.iter().map(Value::as_int).collect()),
+                _                 => Faces::Range(self.as_int()),
+            } // match ..
+        } // fn ..
+
+
+        fn as_bool(&self) -> bool {
+            match self {
+                Value::Int(int)   => *int != 0i16,
+                Value::Bool(bool) => *bool,
+                Value::Text(text) => !text.is_empty(),
+                Value::List(list) => !list.is_empty(),
+                Value::Record(rc) => !rc.is_empty(),
+                _                 => false,
+            } // match ..
+        } // fn ..
+
+
+        fn as_text(&self) -> Result<Value, InterpreterErr> {
+            match self {
+                Value::Int(int)   => Ok(Value::Text(Rc::from(int.to_string()))),
+                Value::Bool(bool) => Ok(Value::Text(Rc::from(bool.to_string()))),
+                Value::Text(..)   => Ok(self.clone()),
+                Value::Void       => Ok(Value::Text(Rc::from(""))),
+                _                 => Err(InterpreterErr::NotAText),
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the item lying at a given index of a list.
+        fn item(items: &[Value], index: i16) -> Result<&Value, InterpreterErr> {
+            usize::try_from(index).ok()
+                .and_then(|i| items.get(i))
+                .ok_or(InterpreterErr::IndexOutOfBounds(index, items.len()))
+        } // fn ..
+
+
+        /// Returns the mutable item lying at a given index of a list.
+        fn item_mut(items: &mut [Value], index: i16) -> Result<&mut Value, InterpreterErr> {
+            let len = items.len();
+            usize::try_from(index).ok()
+                .and_then(|i| items.get_mut(i))
+                .ok_or(InterpreterErr::IndexOutOfBounds(index, len))
+        } // fn ..
+
+
+        /// Returns the field lying at the end of a path of nested records.
+        fn field(&self, path: &[Symbol]) -> Result<&Value, InterpreterErr> {
+            path.iter().try_fold(self, |value, name| match value {
+                Value::Record(fields) => fields.iter()
+                    .find(|(field, _)| field == name)
+                    .map(|(_, value)| value)
+                    .ok_or(InterpreterErr::UnknownField(*name)),
+                _                     => Err(InterpreterErr::NotARecord),
+            }) // try_fold()
+        } // fn ..
+
+
+        /// Returns the mutable field lying at the end of a path of nested records, ready to be written to.
+        fn field_mut(&mut self, path: &[Symbol]) -> Result<&mut Value, InterpreterErr> {
+            path.iter().try_fold(self, |value, name| match value {
+                Value::Record(fields) => Rc::make_mut(fields).iter_mut()
+                    .find(|(field, _)| field == name)
+                    .map(|(_, value)| value)
+                    .ok_or(InterpreterErr::UnknownField(*name)),
+                _                     => Err(InterpreterErr::NotARecord),
+            }) // try_fold()
+        } // fn ..
+    } // impl ..
+
+
+    impl Program {
+        /// Compiles a chain of statements along with every procedure stored in the context.
+        pub fn compile(context: &SyntaxContext, stms: &[Stm]) -> Self {
+
+            let mut compiler = Compiler {
+                code:    Vec::default(),
+                slots:   HashMap::default(),
+                names:   Vec::default(),
+                funcs:   Vec::default(),
+                procs:   HashMap::default(),
+                tables:  Vec::default(),
+                consts:  Vec::default(),
+                pending: Vec::default(),
+                depth:   0usize,
+                blocks:  0usize,
+            }; // let ..
+
+            compiler.stms(stms, &mut Exit::Halt);
+            let end = compiler.code.len();
+            compiler.code.push(Instr::Halt);
+
+            for (name, primitive) in context.registers() {
+                compiler.slot(name);
+                compiler.known(primitive);
+            } // for ..
+
+            while let Some(pending) = compiler.pending.pop() { compiler.body(pending) }
+
+
+            Program {
+                code:   compiler.code,
+                end,
+                names:  compiler.names,
+                slots:  compiler.slots,
+                funcs:  compiler.funcs,
+                procs:  compiler.procs,
+                tables: compiler.tables,
+                consts: compiler.consts,
+            } // Program ..
+        } // fn ..
+
+
+        /// Runs the program against the registers of the context, returning the value of its top-level `out` statement or `Void` when it has none.
+        pub fn run(&self, context: &mut SyntaxContext) -> Result<Primitive, InterpreterErr> {
+            self.start(context, |_, _| Ok(0usize))
+        } // fn ..
+
+
+        /// Calls the procedure stored in a given register with some arguments instead of running the program, returning its output.
+        pub fn call(
+            &self,
+            context: &mut SyntaxContext,
+            name:    &Symbol,
+            args:    Vec<Primitive>,
+        ) -> Result<Primitive, InterpreterErr> {
+
+            let slot = *self.slots.get(name).ok_or(InterpreterErr::UninitReg(*name))?;
+            self.start(context, |machine, context| {
+                let args = args.iter().map(|arg| machine.value(arg).unwrap_or(Value::Void)).collect();
+                machine.call(context, machine.slots[slot].clone(), args, self.end)
+            }) // start()
+        } // fn ..
+
+
+        /// Runs the program against the registers of the context from the entry given by a function, returning the value it halts with.
+        fn start(
+            &self,
+            context: &mut SyntaxContext,
+            entry:   impl FnOnce(&mut Machine, &SyntaxContext) -> Result<usize, InterpreterErr>,
+        ) -> Result<Primitive, InterpreterErr> {
+
+            context.reset_usage();
+
+            let mut machine = Machine {
+                program:  self,
+                slots:    Vec::default(),
+                stack:    Vec::default(),
+                calls:    Vec::default(),
+                scopes:   Vec::default(),
+                tallies:  Vec::default(),
+                live:     context.registers().len(),
+            }; // let ..
+
+            machine.slots = self.names.iter()
+                .map(|name| context.reg(name).ok().and_then(|primitive| machine.value(primitive)))
+                .collect();
+
+            let result = entry(&mut machine, context)
+                .and_then(|pc| machine.exec(context, pc))
+                .map(|value| value.map_or(Primitive::Void, |value| machine.primitive(&value)));
+
+            for (name, slot) in self.names.iter().zip(machine.slots.iter()) {
+                match slot {
+                    Some(value) => context.new_reg(name, machine.primitive(value))?,
+                    None        => context.del_reg(name),
+                } // match ..
+            } // for ..
+
+            result
+        } // fn ..
+
+
+        /// Returns the compiled body of a procedure, found by the body it shares with the one compiled.
+        fn func(&self, proc: &Proc) -> Option<usize> { self.procs.get(&Compiler::key(proc)).copied() }
+
+
+        /// Returns the compiled version of a table.
+        fn table(&self, table: &Table) -> Option<usize> {
+            self.tables.iter().position(|compiled| compiled.table == *table)
+        } // fn ..
+    } // impl ..
+
+
+    impl CompiledTable {
+        /// Returns the body of the row matching a given roll.
+        fn row(&self, roll: i16) -> Result<usize, InterpreterErr> {
+            self.rows.iter()
+                .find(|(from, to, _)| (*from..=*to).contains(&roll))
+                .map(|(_, _, func)| *func)
+                .ok_or(InterpreterErr::NoTableRow(roll))
+        } // fn ..
+    } // impl ..
+
+
+    impl Machine<'_> {
+        /// Converts a primitive into a value, sharing the captures of procedures.
+        fn value(&self, primitive: &Primitive) -> Option<Value> {
+            match primitive {
+                Primitive::Int(int)   => Some(Value::Int(*int)),
+                Primitive::Bool(bool) => Some(Value::Bool(*bool)),
+                Primitive::Text(text) => Some(Value::Text(Rc::from(text.as_str()))),
+                Primitive::Proc(proc) => {
+                    let func     = self.program.func(proc)?;
+                    let captures = proc.captures.iter()
+                        .filter_map(|(name, capture)| Some((*self.program.slots.get(name)?, capture.clone())))
+                        .collect();
+
+                    Some(Value::Proc(Rc::new(Closure { func, captures })))
+                }, // => ..
+                Primitive::List(items) => {
+                    let items = items.iter().map(|item| self.value(item).unwrap_or(Value::Void)).collect();
+                    Some(Value::List(Rc::new(items)))
+                }, // => ..
+                Primitive::Table(table) => self.program.table(table).map(Value::Table),
+                Primitive::Record(rec)  => {
+                    let fields = rec.fields.iter().map(|(name, field)| (*name, self.value(field).unwrap_or(Value::Void))).collect();
+                    Some(Value::Record(Rc::new(fields)))
+                }, // => ..
+                Primitive::Void       => None,
+            } // match ..
+        } // fn ..
+
+
+        /// Converts a value back into a primitive.
+        fn primitive(&self, value: &Value) -> Primitive {
+            match value {
+                Value::Int(int)   => Primitive::Int(*int),
+                Value::Bool(bool) => Primitive::Bool(*bool),
+                Value::Text(text) => Primitive::Text(String::from(&**text)),
+                Value::List(list) => Primitive::List(list.iter().map(|item| self.primitive(item)).collect()),
+                Value::Table(tab) => Primitive::Table(self.program.tables[*tab].table.clone()),
+                Value::Record(rc) => Primitive::Record(Record { fields: rc.iter().map(|(name, field)| (*name, self.primitive(field))).collect() }),
+                Value::Proc(closure) => {
+                    let mut proc = self.program.funcs[closure.func].proc.clone();
+                    proc.captures = closure.captures.iter()
+                        .map(|(slot, capture)| (self.program.names[*slot], capture.clone()))
+                        .collect();
+                    Primitive::Proc(proc)
+                }, // => ..
+                Value::Void       => Primitive::Void,
+            } // match ..
+        } // fn ..
+
+
+        /// Stores a value in a register slot, accounting for the register budget.
+        fn assign(
+            &mut self,
+            context: &SyntaxContext,
+            slot:    usize,
+            value:   Option<Value>,
+        ) -> Result<(), InterpreterErr> {
+            match (&self.slots[slot], &value) {
+                (None, Some(_)) => {
+                    if self.live >= context.limits().registers { return Err(InterpreterErr::RegistersExceeded(context.limits().registers)) }
+                    self.live += 1usize;
+                }, // => ..
+                (Some(_), None) => self.live -= 1usize,
+                _               => (),
+            } // match ..
+
+            self.slots[slot] = value;
+            Ok(())
+        } // fn ..
+
+
+        /// Restores a register slot to a previously saved value, regardless of the register budget.
+        fn restore(&mut self, slot: usize, value: Option<Value>) {
+            match (&self.slots[slot], &value) {
+                (None, Some(_)) => self.live += 1usize,
+                (Some(_), None) => self.live -= 1usize,
+                _               => (),
+            } // match ..
+
+            self.slots[slot] = value;
+        } // fn ..
+
+
+        /// Calls a closure, binding its captures and arguments until it returns and writing its captures back then, and gives the entry to jump to.
+        fn call(
+            &mut self,
+            context: &SyntaxContext,
+            value:   Option<Value>,
+            args:    Vec<Value>,
+            ret:     usize,
+        ) -> Result<usize, InterpreterErr> {
+
+            let closure = match value { Some(Value::Proc(closure)) => closure, _ => return Err(InterpreterErr::NotAProc) };
+            let func    = &self.program.funcs[closure.func];
+
+            if args.len() != func.params.len() { return Err(InterpreterErr::WrongArity(func.params.len(), args.len())) }
+
+            let bindings = closure.captures.iter()
+                .map(|(slot, capture)| (*slot, self.value(&capture.get()).unwrap_or(Value::Void)))
+                .chain(func.params.iter().cloned().zip(args))
+                .collect::<Vec<(usize, Value)>>();
+
+            let entry = self.enter(context, func.entry, bindings, ret)?;
+            if let Some(frame) = self.calls.last_mut() { frame.cells = closure.captures.clone() }
+            Ok(entry)
+        } // fn ..
+
+
+        /// Calls a procedure or rolls a table, and gives the entry to jump to.
+        fn invoke(
+            &mut self,
+            context: &SyntaxContext,
+            value:   Value,
+            ret:     usize,
+        ) -> Result<usize, InterpreterErr> {
+            match value {
+                Value::Table(table) => self.enter(context, self.program.tables[table].entry, Vec::default(), ret),
+                value               => self.call(context, Some(value), Vec::default(), ret),
+            } // match ..
+        } // fn ..
+
+
+        /// Enters a body, binding the given registers until it returns, and gives the entry to jump to.
+        fn enter(
+            &mut self,
+            context:  &SyntaxContext,
+            entry:    usize,
+            bindings: Vec<(usize, Value)>,
+            ret:      usize,
+        ) -> Result<usize, InterpreterErr> {
+
+            if self.calls.len() >= context.limits().call_depth { return Err(InterpreterErr::CallDepthExceeded(context.limits().call_depth)) }
+
+            let mut saved = Vec::with_capacity(bindings.len());
+            for (slot, value) in bindings {
+                saved.push((slot, self.slots[slot].clone()));
+                self.assign(context, slot, match value { Value::Void => None, value => Some(value) })?;
+            } // for ..
+
+            self.calls.push(Frame { ret, saved, cells: Vec::default() });
+            Ok(entry)
+        } // fn ..
+
+
+        /// Pushes an item read as the given type, or calls or rolls it and gives the entry to jump to when it is a procedure or a table and none is expected.
+        fn read(
+            &mut self,
+            context: &SyntaxContext,
+            id:      &PrimitiveId,
+            item:    Value,
+            ret:     usize,
+        ) -> Result<Option<usize>, InterpreterErr> {
+            match item {
+                Value::Proc(..) | Value::Table(..) if !matches!(id, PrimitiveId::Proc | PrimitiveId::Table) => self.invoke(context, item, ret).map(Some),
+                item                                                                                        => { self.stack.push(item); Ok(None) },
+            } // match ..
+        } // fn ..
+
+
+        /// Returns the items of the list stored in a register slot, ready to be written to.
+        fn list(&mut self, slot: usize) -> Result<&mut Vec<Value>, InterpreterErr> {
+            match &mut self.slots[slot] {
+                Some(Value::List(list)) => Ok(Rc::make_mut(list)),
+                Some(_)                 => Err(InterpreterErr::NotAList),
+                None                    => Err(InterpreterErr::UninitReg(self.program.names[slot])),
+            } // match ..
+        } // fn ..
+
+
+        /// Executes the bytecode from a given instruction until it halts, returning the value an `out` statement left on the stack.
+        fn exec(&mut self, context: &mut SyntaxContext, pc: usize) -> Result<Option<Value>, InterpreterErr> {
+
+            let program = self.program;
+            let mut pc  = pc;
+
+            macro_rules! pop { () => { self.stack.pop().unwrap_or(Value::Void) }; }
+
+
+            loop {
+                match program.code[pc] {
+                    Instr::Int(int)   => self.stack.push(Value::Int(int)),
+                    Instr::Bool(bool) => self.stack.push(Value::Bool(bool)),
+                    Instr::Text(ref text) => self.stack.push(Value::Text(text.clone())),
+                    Instr::Void       => self.stack.push(Value::Void),
+                    Instr::Const(i)   => { let a = self.value(&program.consts[i]).unwrap_or(Value::Void); self.stack.push(a) },
+
+                    Instr::Load(slot) => match self.slots[slot].clone() {
+                        Some(value @ (Value::Proc(..) | Value::Table(..))) => { pc = self.invoke(context, value, pc + 1usize)?; continue; },
+                        Some(value)                                        => self.stack.push(value),
+                        None                                               => return Err(InterpreterErr::UninitReg(program.names[slot])),
+                    }, // => ..
+                    Instr::Ask(ref id, ref prompt, range) => {
+                        let answer = context.ask(id, prompt, range)?;
+                        let answer = self.value(&answer).unwrap_or(Value::Void);
+                        self.stack.push(answer);
+                    }, // => ..
+                    Instr::Fetch(slot) => match self.slots[slot].clone() {
+                        Some(value) => self.stack.push(value),
+                        None        => return Err(InterpreterErr::UninitReg(program.names[slot])),
+                    }, // => ..
+                    Instr::Call(slot, argc, ref id) => match self.slots[slot].clone() {
+                        Some(Value::List(list)) => {
+                            if argc != 1usize { return Err(InterpreterErr::WrongArity(1usize, argc)) }
+                            let item = Value::item(&list, pop!().as_int())?.clone();
+                            if let Some(entry) = self.read(context, id, item, pc + 1usize)? { pc = entry; continue; }
+                        }, // => ..
+                        Some(Value::Table(table)) => {
+                            if argc != 1usize { return Err(InterpreterErr::WrongArity(1usize, argc)) }
+                            let row = program.tables[table].row(pop!().as_int())?;
+                            pc = self.enter(context, program.funcs[row].entry, Vec::default(), pc + 1usize)?;
+                            continue;
+                        }, // => ..
+                        Some(value) => {
+                            let args = self.stack.split_off(self.stack.len() - argc);
+                            pc = self.call(context, Some(value), args, pc + 1usize)?;
+                            continue;
+                        }, // => ..
+                        None => match context.find_host(&program.names[slot]) {
+                            Some(host) => {
+                                let args   = self.stack.split_off(self.stack.len() - argc).iter().map(|arg| self.primitive(arg)).collect();
+                                let output = host.call(args)?;
+                                let output = self.value(&output).unwrap_or(Value::Void);
+                                self.stack.push(output);
+                            }, // => ..
+                            None => return Err(InterpreterErr::UninitReg(program.names[slot])),
+                        }, // => ..
+                    }, // => ..
+                    Instr::Cast(ref id) => { let a = pop!(); self.stack.push(match (id, a) {
+                        (PrimitiveId::Int | PrimitiveId::Bool | PrimitiveId::Text, a @ (Value::Proc(..) | Value::Table(..))) => { pc = self.invoke(context, a, pc)?; continue; },
+                        (PrimitiveId::Int,    a)                     => Value::Int(a.as_int()),
+                        (PrimitiveId::Bool,   a)                     => Value::Bool(a.as_bool()),
+                        (PrimitiveId::Text,   a)                     => a.as_text()?,
+                        (PrimitiveId::Void,   _)                     => Value::Void,
+                        (PrimitiveId::Proc,   a @ Value::Proc(..))   => a,
+                        (PrimitiveId::Proc,   _)                     => return Err(InterpreterErr::NotAProc),
+                        (PrimitiveId::List,   a @ Value::List(..))   => a,
+                        (PrimitiveId::List,   _)                     => return Err(InterpreterErr::NotAList),
+                        (PrimitiveId::Table,  a @ Value::Table(..))  => a,
+                        (PrimitiveId::Table,  _)                     => return Err(InterpreterErr::NotATable),
+                        (PrimitiveId::Record, a @ Value::Record(..)) => a,
+                        (PrimitiveId::Record, _)                     => return Err(InterpreterErr::NotARecord),
+                    })}, // => ..
+
+                    Instr::Let(slot) => match pop!() {
+                        Value::Void => self.assign(context, slot, None)?,
+                        value       => self.assign(context, slot, Some(value))?,
+                    }, // => ..
+                    Instr::Set(slot) => match pop!() {
+                        Value::Void => self.restore(slot, None),
+                        value       => match self.slots[slot] {
+                            Some(_) => self.slots[slot] = Some(value),
+                            None    => return Err(InterpreterErr::UninitReg(program.names[slot])),
+                        }, // => ..
+                    }, // => ..
+                    Instr::Macro(name, ref value) => context.new_macro(&name, value.clone()),
+                    Instr::Def(slot, func) => self.assign(context, slot, Some(Value::Proc(Rc::new(Closure { func, captures: Vec::default() }))))?,
+                    Instr::Table(slot, table) => self.assign(context, slot, Some(Value::Table(table)))?,
+                    Instr::Lookup(table)      => { pc = program.funcs[program.tables[table].row(pop!().as_int())?].entry; continue; },
+                    Instr::Lambda(func) => {
+                        let captures = program.funcs[func].captures.iter()
+                            .filter_map(|slot| self.slots[*slot].as_ref().map(|value| (*slot, Capture::new(self.primitive(value)))))
+                            .collect();
+                        self.stack.push(Value::Proc(Rc::new(Closure { func, captures })));
+                    }, // => ..
+
+                    Instr::List(len) => {
+                        let items = self.stack.split_off(self.stack.len() - len);
+                        self.stack.push(Value::List(Rc::new(items)));
+                    }, // => ..
+                    Instr::Store(slot)  => { let a = pop!(); let index = pop!().as_int(); *Value::item_mut(self.list(slot)?, index)? = a },
+                    Instr::Append(slot) => { let a = pop!(); self.list(slot)?.push(a) },
+                    Instr::Remove(slot) => { let index = pop!().as_int(); let list = self.list(slot)?; Value::item(list, index)?; list.remove(index as usize); },
+
+                    Instr::Record(ref names) => {
+                        let fields = self.stack.split_off(self.stack.len() - names.len());
+                        self.stack.push(Value::Record(Rc::new(names.iter().cloned().zip(fields).collect())));
+                    }, // => ..
+                    Instr::Field(slot, ref path, ref id) => match &self.slots[slot] {
+                        Some(value) => {
+                            let item = value.field(path)?.clone();
+                            if let Some(entry) = self.read(context, id, item, pc + 1usize)? { pc = entry; continue; }
+                        }, // => ..
+                        None => return Err(InterpreterErr::UninitReg(program.names[slot])),
+                    }, // => ..
+                    Instr::Update(slot, ref path) => { let a = pop!(); match &mut self.slots[slot] {
+                        Some(value) => *value.field_mut(path)? = a,
+                        None        => return Err(InterpreterErr::UninitReg(program.names[slot])),
+                    }}, // => ..
+
+                    Instr::Save(slot)    => { let a = self.slots[slot].clone().unwrap_or(Value::Void); self.stack.push(a) },
+                    Instr::Restore(slot) => match pop!() {
+                        Value::Void => self.restore(slot, None),
+                        value       => self.restore(slot, Some(value)),
+                    }, // => ..
+                    Instr::Enter(ref slots) => self.scopes.push(slots.iter().map(|slot| (*slot, self.slots[*slot].clone())).collect()),
+                    Instr::Leave(n)         => for _ in 0usize..n {
+                        let saved = self.scopes.pop().unwrap_or_default();
+                        saved.into_iter().rev().for_each(|(slot, value)| self.restore(slot, value));
+                    }, // => ..
+                    Instr::Pop   => { pop!(); },
+                    Instr::Items => match pop!() {
+                        Value::List(list) => self.stack.push(Value::List(Rc::new(list.iter().rev().cloned().collect()))),
+                        _                 => return Err(InterpreterErr::NotAList),
+                    }, // => ..
+                    Instr::Next(ref id, end) => {
+                        let item = match self.stack.last_mut() { Some(Value::List(list)) => Rc::make_mut(list).pop(), _ => None };
+                        match item {
+                            Some(item) => if let Some(entry) = self.read(context, id, item, pc + 1usize)? { pc = entry; continue; },
+                            None       => { self.stack.pop(); pc = end; continue; },
+                        } // match ..
+                    }, // => ..
+
+                    Instr::Runs => match self.stack.last() {
+                        Some(Value::Int(runs)) if *runs > 0i16 => (),
+                        runs                                   => return Err(InterpreterErr::NoRuns(runs.map_or(0i16, Value::as_int))),
+                    }, // => ..
+                    Instr::Sample(end) => {
+                        let top = self.stack.len() - 2usize;
+                        match self.stack[top] {
+                            Value::Int(runs) if runs > 0i16 => self.stack[top] = Value::Int(runs - 1i16),
+                            _                               => { pc = end; continue; },
+                        } // match ..
+                    }, // => ..
+                    Instr::Collect => { let a = pop!(); if let Some(Value::List(list)) = self.stack.last_mut() { Rc::make_mut(list).push(a) } },
+
+                    Instr::Count(inclusive, end) => {
+                        let top                 = self.stack.len() - 3usize;
+                        let (counter, to, step) = (&self.stack[top], self.stack[top + 1usize].as_int(), self.stack[top + 2usize].as_int());
+                        if step == 0i16 { return Err(InterpreterErr::ZeroStep) }
+
+                        match counter {
+                            Value::Int(i) if Bounds::covers(inclusive, *i, to, step) => {
+                                let i = *i;
+                                self.stack[top] = i.checked_add(step).map_or(Value::Void, Value::Int);
+                                self.stack.push(Value::Int(i));
+                            }, // => ..
+                            _ => { self.stack.truncate(top); pc = end; continue; },
+                        } // match ..
+                    }, // => ..
+
+                    Instr::Neg => { let a = pop!().as_int(); self.stack.push(Value::Int(Op::checked(a.checked_neg())?)) },
+                    Instr::Len => match pop!() {
+                        Value::List(list) => self.stack.push(Value::Int(list.len() as i16)),
+                        _                 => return Err(InterpreterErr::NotAList),
+                    }, // => ..
+                    Instr::Add => { let a = pop!().as_int(); let b = pop!().as_int(); self.stack.push(Value::Int(Op::checked(b.checked_add(a))?)) },
+                    Instr::Sub => { let a = pop!().as_int(); let b = pop!().as_int(); self.stack.push(Value::Int(Op::checked(b.checked_sub(a))?)) },
+                    Instr::Mul => { let a = pop!().as_int(); let b = pop!().as_int(); self.stack.push(Value::Int(Op::checked(b.checked_mul(a))?)) },
+                    Instr::Div => { let a = pop!().as_int(); let b = pop!().as_int(); self.stack.push(Value::Int(Op::divide(b, a)?)) },
+
+                    Instr::Dice(cmp) => {
+                        let c    = match cmp { Some(_) => pop!().as_int(), None => 0i16 };
+                        let n     = pop!().as_int();
+                        let faces = pop!().faces();
+                        context.throw_dice(n)?;
+                        let sum = (0i16..n).try_fold(0i16, |sum, _| {
+                            let dice = faces.throw(context);
+                            Op::checked(sum.checked_add(match cmp {
+                                Some(Cmp::Eq) => (dice == c) as i16,
+                                Some(Cmp::Ne) => (dice != c) as i16,
+                                Some(Cmp::Gt) => (dice >  c) as i16,
+                                Some(Cmp::Ge) => (dice >= c) as i16,
+                                Some(Cmp::Lt) => (dice <  c) as i16,
+                                Some(Cmp::Le) => (dice <= c) as i16,
+                                None          => dice,
+                            })) // checked()
+                        })?; // let ..
+                        self.stack.push(Value::Int(sum))
+                    }, // => ..
+                    Instr::Pool(ref pool) => {
+                        let keep = match pool.keep() { Some(_) => Some(pop!().as_int()), None => None };
+                        let n     = pop!().as_int();
+                        let faces = pop!().faces();
+                        let sum   = pool.throw(context, &faces, n, keep)?;
+                        self.stack.push(Value::Int(sum))
+                    }, // => ..
+                    Instr::Cmp(cmp) => { let a = pop!().as_int(); let b = pop!().as_int(); self.stack.push(Value::Bool(match cmp {
+                        Cmp::Eq => b == a,
+                        Cmp::Ne => b != a,
+                        Cmp::Gt => b >= a,
+                        Cmp::Ge => b >  a,
+                        Cmp::Lt => b <  a,
+                        Cmp::Le => b <= a,
+                    }))}, // => ..
+
+                    Instr::Jump(target)       => { pc = target; continue; },
+                    Instr::JumpUnless(target) => if !pop!().as_bool() { pc = target; continue; },
+                    Instr::Step(_)            => context.step()?,
+                    Instr::Iter               => context.iterate()?,
+
+                    Instr::Tally                 => self.tallies.push(context.open_tally()),
+                    Instr::Tell(span, ref expr)  => {
+                        let a     = pop!();
+                        let value = self.primitive(&a);
+                        let dice  = context.close_tally(self.tallies.pop().unwrap_or_default());
+                        context.tell(TellEvent { value, expr: expr.clone(), span, dice });
+                    }, // => ..
+                    Instr::Assert(index, ref expr) => if !pop!().as_bool() { return Err(InterpreterErr::AssertionFailed(index, Box::new(expr.clone()))) },
+                    Instr::Mean(index, ref expr)   => {
+                        let tolerance = pop!().as_real();
+                        let target    = pop!().as_real();
+                        let outcomes  = match pop!() { Value::List(list) => list, _ => Rc::default() };
+                        let stats     = Stats::of(&outcomes.iter().map(Value::as_int).collect::<Vec<i16>>());
+                        pop!();
+                        if !stats.within(target, tolerance) { return Err(InterpreterErr::MeanOutOfRange(index, Box::new(expr.clone()), Box::new(stats), target, tolerance)) }
+                    }, // => ..
+                    Instr::Never(index, ref expr)  => {
+                        let outcomes = match pop!() { Value::List(list) => list, _ => Rc::default() };
+                        pop!();
+                        match outcomes.iter().filter(|outcome| outcome.as_bool()).count() {
+                            0usize => (),
+                            count  => return Err(InterpreterErr::OutcomeProduced(index, Box::new(expr.clone()), count, outcomes.len())),
+                        } // match ..
+                    }, // => ..
+                    Instr::Ret  => match self.calls.pop() {
+                        Some(frame) => {
+                            for (slot, capture) in frame.cells { capture.set(self.slots[slot].as_ref().map_or(Primitive::Void, |value| self.primitive(value))) }
+                            frame.saved.into_iter().rev().for_each(|(slot, value)| self.restore(slot, value));
+                            pc = frame.ret;
+                            continue;
+                        }, // => ..
+                        None => return Ok(self.stack.pop()),
+                    }, // => ..
+                    Instr::Halt => return Ok(self.stack.pop()),
+                } // match ..
+
+                pc += 1usize;
+            } // loop ..
+        } // fn ..
+    } // impl ..
+
+
+    impl Compiler {
+        /// Returns the slot of a register, allocating it if needed.
+        fn slot(&mut self, name: &Symbol) -> usize {
+            match self.slots.get(name) {
+                Some(slot) => *slot,
+                None       => {
+                    let slot = self.names.len();
+                    self.slots.insert(*name, slot);
+                    self.names.push(*name);
+                    slot
+                }, // => ..
+            } // match ..
+        } // fn ..
+
+
+        /// Queues a new procedure body with the given parameters and declaration for compilation.
+        fn func(&mut self, params: &[Symbol], decl: &Decl) -> usize {
+            self.proc(&Proc { params: params.to_vec(), captures: Vec::default(), body: Arc::new(decl.clone()) })
+        } // fn ..
+
+
+        /// Returns the compiled body of a procedure, queuing it for compilation if no procedure sharing its body was queued yet.
+        fn proc(&mut self, proc: &Proc) -> usize {
+
+            if let Some(func) = self.procs.get(&Self::key(proc)) { return *func }
+
+            let params   = proc.params.iter().map(|param| self.slot(param)).collect::<Vec<usize>>();
+            let captures = proc.body.refs().iter().map(|name| self.slot(name)).filter(|slot| !params.contains(slot)).collect();
+            let proc     = Proc { captures: Vec::default(), ..proc.clone() };
+
+            self.procs.insert(Self::key(&proc), self.funcs.len());
+            self.funcs.push(Func { entry: 0usize, params, captures, proc });
+            self.pending.push(Pending::Func(self.funcs.len() - 1usize));
+            self.funcs.len() - 1usize
+        } // fn ..
+
+
+        /// Returns the key a compiled procedure is found by: the address of its body, shared by every copy of it.
+        fn key(proc: &Proc) -> usize { Arc::as_ptr(&proc.body) as usize }
+
+
+        /// Returns the compiled table matching the given one, queuing its dice and its rows for compilation if needed.
+        fn table(&mut self, table: &Table) -> usize {
+
+            if let Some(compiled) = self.tables.iter().position(|compiled| compiled.table == *table) { return compiled }
+
+            let rows = table.rows.iter().map(|(from, to, decl)| (*from, *to, self.func(&[], decl))).collect();
+
+            self.tables.push(CompiledTable { entry: 0usize, rows, table: table.clone() });
+            self.pending.push(Pending::Table(self.tables.len() - 1usize));
+            self.tables.len() - 1usize
+        } // fn ..
+
+
+        /// Queues every procedure held by a primitive already stored in the context, along with the ones they captured.
+        fn known(&mut self, primitive: &Primitive) {
+            match primitive {
+                Primitive::Proc(proc) => {
+                    self.proc(proc);
+                    for (name, capture) in &proc.captures {
+                        self.slot(name);
+                        self.known(&capture.get());
+                    } // for ..
+                }, // => ..
+                Primitive::List(items)  => items.iter().for_each(|item| self.known(item)),
+                Primitive::Table(table) => { self.table(table); },
+                Primitive::Record(rec)  => rec.fields.iter().for_each(|(_, field)| self.known(field)),
+                _                       => (),
+            } // match ..
+        } // fn ..
+
+
+        /// Compiles a queued procedure body or table dice.
+        fn body(&mut self, pending: Pending) {
+            match pending {
+                Pending::Func(func) => {
+                    self.funcs[func].entry = self.code.len();
+                    let decl = self.funcs[func].proc.body.clone();
+
+                    match &*decl {
+                        Decl::Expr(expr)    => self.expr(expr),
+                        Decl::Closure(_)    => { self.block(&decl, &mut Exit::Ret); self.code.push(Instr::Void) },
+                    } // match ..
+
+                    self.code.push(Instr::Ret);
+                }, // => ..
+                Pending::Table(table) => {
+                    self.tables[table].entry = self.code.len();
+                    let dice = self.tables[table].table.dice.clone();
+
+                    self.expr(&dice);
+                    self.code.push(Instr::Lookup(table));
+                }, // => ..
+            } // match ..
+        } // fn ..
+
+
+        fn stms(&mut self, stms: &[Stm], exit: &mut Exit) {
+            stms.iter().for_each(|stm| self.stm(stm, exit))
+        } // fn ..
+
+
+        fn stm(&mut self, stm: &Stm, exit: &mut Exit) {
+            self.code.push(Instr::Step(stm.index()));
+            match stm {
+                Stm::RegLet(_, name, decl) => { self.decl(decl); let slot = self.slot(name); self.code.push(Instr::Let(slot)) },
+                Stm::RegSet(_, name, decl) => { self.decl(decl); let slot = self.slot(name); self.code.push(Instr::Set(slot)) },
+                Stm::Macro(_, name, params, body) => if self.blocks == 0usize { self.code.push(Instr::Macro(*name, Macro { params: params.clone(), body: body.clone() })) },
+                Stm::Define(_, name, params, decl) => {
+                    let func = self.func(params, decl);
+                    let slot = self.slot(name);
+                    self.code.push(Instr::Def(slot, func));
+                }, // => ..
+                Stm::Table(_, name, table) => {
+                    let table = self.table(table);
+                    let slot  = self.slot(name);
+                    self.code.push(Instr::Table(slot, table));
+                }, // => ..
+                Stm::ItemSet(_, name, at, decl)    => { self.scalar(at, PrimitiveId::Int); self.decl(decl); let slot = self.slot(name); self.code.push(Instr::Store(slot)) },
+                Stm::FieldSet(_, name, path, decl) => { self.decl(decl); let slot = self.slot(name); self.code.push(Instr::Update(slot, path.clone())) },
+                Stm::Append(_, name, decl)         => { self.decl(decl); let slot = self.slot(name); self.code.push(Instr::Append(slot)) },
+                Stm::Remove(_, name, at)           => { self.scalar(at, PrimitiveId::Int); let slot = self.slot(name); self.code.push(Instr::Remove(slot)) },
+                Stm::If(_, expr, decl)     => {
+                    self.scalar(expr, PrimitiveId::Bool);
+                    let jump = self.code.len();
+                    self.code.push(Instr::JumpUnless(0usize));
+
+                    match decl {
+                        Decl::Expr(expr)    => self.out(expr, exit),
+                        Decl::Closure(_)    => self.block(decl, exit),
+                    } // match ..
+
+                    self.code[jump] = Instr::JumpUnless(self.code.len());
+                }, // => ..
+                Stm::While(_, expr, decl)  => {
+                    let head = self.code.len();
+                    self.scalar(expr, PrimitiveId::Bool);
+                    let jump = self.code.len();
+                    self.code.push(Instr::JumpUnless(0usize));
+                    self.code.push(Instr::Iter);
+
+                    if let Decl::Closure(_) = decl { self.block(decl, &mut Exit::Skip(self.depth, head)) }
+
+                    self.code.push(Instr::Jump(head));
+                    self.code[jump] = Instr::JumpUnless(self.code.len());
+                }, // => ..
+                Stm::ForEach(_, id, name, expr, decl) => {
+                    let slot = self.slot(name);
+                    self.code.push(Instr::Save(slot));
+                    self.expr(expr);
+                    self.code.push(Instr::Items);
+
+                    let head = self.code.len();
+                    self.code.push(Instr::Next(id.clone(), 0usize));
+                    self.code.push(Instr::Cast(id.clone()));
+                    self.code.push(Instr::Let(slot));
+                    self.code.push(Instr::Iter);
+
+                    if let Decl::Closure(_) = decl { self.block(decl, &mut Exit::Skip(self.depth, head)) }
+
+                    self.code.push(Instr::Jump(head));
+                    self.code[head] = Instr::Next(id.clone(), self.code.len());
+                    self.code.push(Instr::Restore(slot));
+                }, // => ..
+                Stm::For(_, name, bounds, decl) => {
+                    let slot = self.slot(name);
+                    self.code.push(Instr::Save(slot));
+                    self.expr(&bounds.from);
+                    self.code.push(Instr::Cast(PrimitiveId::Int));
+                    self.scalar(&bounds.to, PrimitiveId::Int);
+                    match &bounds.step {
+                        Some(step) => self.scalar(step, PrimitiveId::Int),
+                        None       => self.code.push(Instr::Int(1i16)),
+                    } // match ..
+
+                    let head = self.code.len();
+                    self.code.push(Instr::Count(bounds.inclusive, 0usize));
+                    self.code.push(Instr::Let(slot));
+                    self.code.push(Instr::Iter);
+
+                    if let Decl::Closure(_) = decl { self.block(decl, &mut Exit::Skip(self.depth, head)) }
+
+                    self.code.push(Instr::Jump(head));
+                    self.code[head] = Instr::Count(bounds.inclusive, self.code.len());
+                    self.code.push(Instr::Restore(slot));
+                }, // => ..
+                Stm::Out(_, expr)          => self.out(expr, exit),
+                Stm::Tell(_, expr, span)   => { self.code.push(Instr::Tally); self.expr(expr); self.code.push(Instr::Tell(*span, expr.clone())) },
+                Stm::Assert(index, expr)   => { self.scalar(expr, PrimitiveId::Bool); self.code.push(Instr::Assert(*index, expr.clone())) },
+                Stm::Mean(index, sample, target, tolerance) => {
+                    self.sample(sample, PrimitiveId::Int);
+                    self.bound(target);
+                    self.bound(tolerance);
+                    self.code.push(Instr::Mean(*index, sample.expr.clone()));
+                }, // => ..
+                Stm::Never(index, sample) => {
+                    self.sample(sample, PrimitiveId::Bool);
+                    self.code.push(Instr::Never(*index, sample.expr.clone()));
+                }, // => ..
+            } // match ..
+        } // fn ..
+
+
+        /// Compiles the bound of a statistical assertion, leaving its value on the stack.
+        fn bound(&mut self, bound: &Bound) {
+            match bound {
+                Bound::Expr(expr)       => { self.expr(expr); self.code.push(Instr::Cast(PrimitiveId::Int)) },
+                Bound::Decimal(decimal) => self.code.push(Instr::Text(Rc::from(decimal.as_str()))),
+            } // match ..
+        } // fn ..
+
+
+        /// Compiles a sampling loop, leaving the remaining runs and the list of outcomes read as a given type on the stack.
+        fn sample(&mut self, sample: &Sample, id: PrimitiveId) {
+            self.expr(&sample.runs);
+            self.code.push(Instr::Cast(PrimitiveId::Int));
+            self.code.push(Instr::Runs);
+            self.code.push(Instr::List(0usize));
+
+            let head = self.code.len();
+            self.code.push(Instr::Sample(0usize));
+            self.code.push(Instr::Iter);
+            match id {
+                PrimitiveId::Int => { self.expr(&sample.expr); self.code.push(Instr::Cast(id)) },
+                id               => self.scalar(&sample.expr, id),
+            } // match ..
+            self.code.push(Instr::Collect);
+
+            self.code.push(Instr::Jump(head));
+            self.code[head] = Instr::Sample(self.code.len());
+        } // fn ..
+
+
+        /// Compiles the statements of a nested declaration, restoring the procedures it defines once it is left.
+        /// The macros it defines are left out, as they would be forgotten with the block before any later script could expand them.
+        fn block(&mut self, decl: &Decl, exit: &mut Exit) {
+
+            let Decl::Closure(stms) = decl else { return };
+            let slots = decl.defines().iter().map(|name| self.slot(name)).collect::<Vec<usize>>();
+            self.blocks += 1usize;
+
+            if slots.is_empty() { self.stms(stms, exit) } else {
+                self.code.push(Instr::Enter(slots));
+                self.depth += 1usize;
+                self.stms(stms, exit);
+                self.depth -= 1usize;
+                self.code.push(Instr::Leave(1usize));
+            } // if ..
+            self.blocks -= 1usize;
+        } // fn ..
+
+
+        /// Compiles an `out` statement with respect to where it leads to, leaving the scopes opened since.
+        fn out(&mut self, expr: &Expr, exit: &mut Exit) {
+
+            let base = match exit { Exit::Halt | Exit::Ret => 0usize, Exit::Yield(base, _) | Exit::Skip(base, _) => *base };
+            self.expr(expr);
+            if self.depth > base { self.code.push(Instr::Leave(self.depth - base)) }
+
+            match exit {
+                Exit::Halt              => self.code.push(Instr::Halt),
+                Exit::Ret               => self.code.push(Instr::Ret),
+                Exit::Skip(_, head)     => { self.code.push(Instr::Pop); self.code.push(Instr::Jump(*head)) },
+                Exit::Yield(_, patches) => { patches.push(self.code.len()); self.code.push(Instr::Jump(0usize)) },
+            } // match ..
+        } // fn ..
+
+
+        /// Compiles a declaration leaving its output on the stack.
+        fn decl(&mut self, decl: &Decl) {
+            match decl {
+                Decl::Expr(expr)    => self.expr(expr),
+                Decl::Closure(_)    => {
+                    let mut exit = Exit::Yield(self.depth, Vec::default());
+                    self.block(decl, &mut exit);
+                    self.code.push(Instr::Void);
+
+                    if let Exit::Yield(_, patches) = exit {
+                        let end = self.code.len();
+                        patches.into_iter().for_each(|patch| self.code[patch] = Instr::Jump(end));
+                    } // if ..
+                }, // => ..
+            } // match ..
+        } // fn ..
+
+
+        /// Compiles an expression, walking its operators iteratively.
+        fn expr(&mut self, expr: &Expr) {
+
+            /// A step of the compilation, operands being compiled before the operator applying to them.
+            enum Task<'e> { Expr(&'e Expr), Val(&'e Val), Int(&'e Val), Instr(Instr) }
+
+
+            let mut tasks = vec![Task::Expr(expr)];
+            while let Some(task) = tasks.pop() {
+                match task {
+                    Task::Expr(Expr::Val(a))  => tasks.push(Task::Val(a)),
+                    Task::Val(Val::Expr(a))   => tasks.push(Task::Expr(a)),
+                    Task::Val(a)              => self.val(a),
+                    Task::Int(a)              => self.cast(a, PrimitiveId::Int),
+                    Task::Instr(instr)        => self.code.push(instr),
+                    Task::Expr(Expr::Op(opr)) => {
+                        let b = match opr {
+                            Op::Neg(a)                  => { tasks.extend([Task::Instr(Instr::Neg), Task::Int(a), Task::Val(a)]); continue; },
+                            Op::Len(a)                  => { tasks.extend([Task::Instr(Instr::Len), Task::Val(a)]); continue; },
+                            Op::Add(a, b)               => { tasks.extend([Task::Instr(Instr::Add), Task::Int(a), Task::Val(a)]); b },
+                            Op::Sub(a, b)               => { tasks.extend([Task::Instr(Instr::Sub), Task::Int(a), Task::Val(a)]); b },
+                            Op::Mul(a, b)               => { tasks.extend([Task::Instr(Instr::Mul), Task::Int(a), Task::Val(a)]); b },
+                            Op::Div(a, b)               => { tasks.extend([Task::Instr(Instr::Div), Task::Int(a), Task::Val(a)]); b },
+                            Op::Cmp(cmp, b)             => { tasks.extend([Task::Instr(Instr::Cmp(Cmp::from(cmp))), Task::Int(cmp.val()), Task::Val(cmp.val())]); b },
+                            Op::Dice(a, b, c)           => {
+                                tasks.push(Task::Instr(Instr::Dice(c.as_ref().map(Cmp::from))));
+                                if let Some(c) = c { tasks.extend([Task::Int(c.val()), Task::Val(c.val())]) }
+                                if let Expr::Val(b) = &**b { tasks.push(Task::Int(b)) }
+                                tasks.push(Task::Expr(b));
+                                tasks.extend([Task::Int(a), Task::Val(a)]);
+                                continue;
+                            }, // => ..
+                            Op::Pool(a, b, c)           => {
+                                tasks.push(Task::Instr(Instr::Pool(*c.clone())));
+                                if let Some(c) = c.keep() { tasks.extend([Task::Int(c), Task::Val(c)]) }
+                                if let Expr::Val(b) = &**b { tasks.push(Task::Int(b)) }
+                                tasks.push(Task::Expr(b));
+                                tasks.extend([Task::Int(a), Task::Val(a)]);
+                                continue;
+                            }, // => ..
+                        }; // let ..
+
+                        if let Expr::Val(b) = &**b { tasks.push(Task::Int(b)) }
+                        tasks.push(Task::Expr(b));
+                    }, // => ..
+                } // match ..
+            } // while ..
+        } // fn ..
+
+
+        /// Compiles an expression read as an integer or a boolean, calling or rolling it when it gives a procedure or a table.
+        fn scalar(&mut self, expr: &Expr, id: PrimitiveId) {
+            self.expr(expr);
+            if let Expr::Val(val) = expr { self.cast(val, id) }
+        } // fn ..
+
+
+        /// Casts an already compiled value when it may give a procedure or a table.
+        fn cast(&mut self, val: &Val, id: PrimitiveId) {
+            if Self::callable(val) { self.code.push(Instr::Cast(id)) }
+        } // fn ..
+
+
+        /// Tells whether a value may give a procedure or a table rather than what it is read as.
+        fn callable(val: &Val) -> bool {
+            match val {
+                Val::Ref(id, _) | Val::Field(id, ..) | Val::Call(id, ..) => matches!(id, PrimitiveId::Proc | PrimitiveId::Table),
+                Val::Lambda(..) | Val::Proc(..) | Val::Table(..)         => true,
+                Val::Expr(expr)                                          => matches!(&**expr, Expr::Val(val) if Self::callable(val)),
+                _                                                        => false,
+            } // match ..
+        } // fn ..
+
+
+        fn val(&mut self, val: &Val) {
+            match val {
+                Val::Int(int)      => self.code.push(Instr::Int(*int)),
+                Val::Bool(bool)    => self.code.push(Instr::Bool(*bool)),
+                Val::Text(text)    => self.code.push(Instr::Text(Rc::from(text.as_str()))),
+                Val::Void          => self.code.push(Instr::Void),
+                Val::Expr(expr)    => self.expr(expr),
+                Val::Ask(id, prompt, range) => self.code.push(Instr::Ask(id.clone(), prompt.clone(), *range)),
+                Val::Ref(id @ (PrimitiveId::Proc | PrimitiveId::Table), name) => {
+                    let slot = self.slot(name);
+                    self.code.push(Instr::Fetch(slot));
+                    self.code.push(Instr::Cast(id.clone()));
+                }, // => ..
+                Val::Ref(id, name) => {
+                    let slot = self.slot(name);
+                    self.code.push(Instr::Load(slot));
+                    self.code.push(Instr::Cast(id.clone()));
+                }, // => ..
+                Val::Call(id, name, args) => {
+                    args.iter().for_each(|arg| self.expr(arg));
+                    let slot = self.slot(name);
+                    self.code.push(Instr::Call(slot, args.len(), id.clone()));
+                    self.code.push(Instr::Cast(id.clone()));
+                }, // => ..
+                Val::Lambda(params, decl) => {
+                    let func = self.func(params, decl);
+                    self.code.push(Instr::Lambda(func));
+                }, // => ..
+                Val::List(items) => {
+                    items.iter().for_each(|item| self.expr(item));
+                    self.code.push(Instr::List(items.len()));
+                }, // => ..
+                Val::Record(fields) => {
+                    fields.iter().for_each(|(_, field)| self.expr(field));
+                    self.code.push(Instr::Record(fields.iter().map(|(name, _)| *name).collect()));
+                }, // => ..
+                Val::Field(id, name, path) => {
+                    let slot = self.slot(name);
+                    self.code.push(Instr::Field(slot, path.clone(), id.clone()));
+                    self.code.push(Instr::Cast(id.clone()));
+                }, // => ..
+                Val::Proc(proc) => {
+                    self.known(&Primitive::Proc(*proc.clone()));
+                    self.consts.push(Primitive::Proc(*proc.clone()));
+                    self.code.push(Instr::Const(self.consts.len() - 1usize));
+                }, // => ..
+                Val::Table(table) => {
+                    self.table(table);
+                    self.consts.push(Primitive::Table(*table.clone()));
+                    self.code.push(Instr::Const(self.consts.len() - 1usize));
+                }, // => ..
+            } // match ..
+        } // fn ..
+    } // impl ..
+
+
+//#####################
+// T E S T S
+//#####################
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{SyntaxContext, SyntaxElement, Stm, Decl, WsToken, Program, Collector, TellEvent, InterpreterErr, Primitive, Symbol, Rng, Expr, Val, Op, Proc, PrimitiveId, Capture, TypeChecker};
+        use std::sync::Arc;
+        use std::collections::{HashMap, HashSet};
+
+        /// What a script leaves behind once run: its result, its registers, its told values and the dice stream.
+        #[derive(Debug, PartialEq)]
+        struct Outcome {
+            result:    Result<(), String>,
+            registers: HashMap<Symbol, String>,
+            told:      Vec<(Primitive, Vec<i16>)>,
+            rng:       Rng,
+        } // struct ..
+
+        /// Runs a script in a fresh seeded context, through the tree interpreter or the virtual machine.
+        fn run(src: &str, vm: bool) -> Outcome {
+
+            let told        = Collector::default();
+            let mut context = SyntaxContext::default();
+            context.set_seed(42u64);
+            context.set_output(told.clone());
+            context.push(src).unwrap();
+
+            let stms   = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+            let result = match vm {
+                true  => Program::compile(&context, &stms).run(&mut context).map(|_| ()),
+                false => Stm::run(&mut context, &Decl::Closure(stms)).map(|_| ()),
+            }; // let ..
+
+            Outcome {
+                result:    result.map_err(|err| err.to_string()),
+                registers: context.registers().iter().map(|(name, value)| (*name, shape(value))).collect(),
+                told:      told.take().into_iter().map(|TellEvent { value, dice, .. }| (value, dice)).collect(),
+                rng:       context.rng.clone(),
+            } // Outcome ..
+        } // fn ..
+
+        /// Describes a value, the cells captured by procedures being compared by what they hold rather than by identity.
+        fn shape(value: &Primitive) -> String {
+            match value {
+                Primitive::Proc(proc)  => format!("{:?} {:?} {:?}", proc.params, proc.captures.iter().map(|(name, cell)| (name, shape(&cell.get()))).collect::<Vec<_>>(), proc.body),
+                Primitive::List(items) => format!("{:?}", items.iter().map(shape).collect::<Vec<_>>()),
+                Primitive::Record(rec) => format!("{:?}", rec.fields.iter().map(|(name, field)| (name, shape(field))).collect::<Vec<_>>()),
+                value                  => format!("{:?}", value),
+            } // match ..
+        } // fn ..
+
+        /// Asserts that both engines agree on a script.
+        fn agree(src: &str) {
+            let (tree, vm) = (run(src, false), run(src, true));
+            assert_eq!(tree, vm, "the engines disagree on `{}`", src);
+        } // fn ..
+
+
+        #[test]
+        fn arithmetic() {
+            agree("let [a] <- 1 + 2 * 3 - 4 / 2; let [b] <- - integer [a] + length list (1, 2); let [c] <- 3 > 2; let [g] <- 2 >= 2;");
+            agree("let [a] <- 7; let [b] <- integer [a] = 7; let [c] <- integer [a] /= 7; let [g] <- integer [a] <= 6; let [e] <- text [a];");
+        } // fn ..
+
+        #[test]
+        fn dice() {
+            agree("let [a] <- 3d6; let [b] <- 2 d 1d6; let [c] <- 10d6:>4; let [g] <- 4 d list (-1, 0, 1); let [e] <- 6d6:<=1d6;");
+            agree("tell -> 2d6 + 1d4; tell -> 3 d list (1, 1, 2) : = 2;");
+        } // fn ..
+
+        #[test]
+        fn custom_dice() {
+            let src = "let [fate] <- list (-1, 0, 1); let [ten] <- list (0, 1, 2, 3, 4, 5, 6, 7, 8, 9); let [uneven] <- list (1, 1, 2, 2, 3, 4); let [none] <- list (); let [f] <- 4 d list [fate]; let [t] <- 1 d list [ten]; let [u] <- 3 d list [uneven] : = 2; let [s] <- 1 d list [uneven]; let [z] <- 2 d list [none];";
+            agree(src);
+
+            let mut seen = HashMap::<&str, HashSet<i16>>::default();
+            for seed in 0u64..500u64 {
+                let mut context = SyntaxContext::default();
+                context.set_seed(seed);
+                context.push(src).unwrap();
+                let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+                Program::compile(&context, &stms).run(&mut context).unwrap();
+
+                for name in ["f", "t", "u", "s", "z"] {
+                    let Primitive::Int(roll) = context.registers()[&Symbol::from(name)] else { panic!("expected an integer") };
+                    seen.entry(name).or_default().insert(roll);
+                } // for ..
+            } // for ..
+
+            for (name, expected) in [("f", -4i16..=4i16), ("t", 0i16..=9i16), ("u", 0i16..=3i16), ("s", 1i16..=4i16), ("z", 0i16..=0i16)] {
+                assert_eq!(seen[name], expected.collect::<HashSet<i16>>(), "`{}`", name);
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn arithmetic_failures() {
+            for (src, err) in [
+                ("let [a] <- 1 / 0;",                    InterpreterErr::DivisionByZero),
+                ("let [a] <- 0; let [b] <- 5 / integer [a];", InterpreterErr::DivisionByZero),
+                ("let [a] <- 30000 + 30000;",            InterpreterErr::Overflow),
+                ("let [a] <- 0 - 30000 - 30000;",        InterpreterErr::Overflow),
+                ("let [a] <- 200 * 200;",                InterpreterErr::Overflow),
+                ("let [a] <- - (0 - 32767 - 1);",        InterpreterErr::Overflow),
+                ("let [a] <- (0 - 32767 - 1) / - 1;",    InterpreterErr::Overflow),
+                ("let [a] <- 10000d6;",                  InterpreterErr::Overflow),
+            ] {
+                agree(src);
+                assert_eq!(run(src, true).result, Err(err.to_string()), "`{}`", src);
+            } // for ..
+
+            for notation in ["10000d6!", "10000d6kh10000"] {
+                let stms = vec![Stm::RegLet(0usize, Symbol::from("a"), Decl::Expr(Expr::from_notation(notation).unwrap()))];
+                let mut tree = SyntaxContext::default();
+                let mut vm   = SyntaxContext::default();
+                assert!(matches!(Stm::run(&mut tree, &Decl::Closure(stms.clone())), Err(InterpreterErr::Overflow)), "`{}` on the tree interpreter", notation);
+                assert!(matches!(Program::compile(&vm, &stms).run(&mut vm), Err(InterpreterErr::Overflow)), "`{}` on the VM", notation);
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn constant_procedures_and_tables() {
+            let mut context = SyntaxContext::default();
+            context.set_seed(7u64);
+            context.push("table [t] | 1d6 |: 1..3 -> 10; 4..6 -> 20; >>>>").unwrap();
+            let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+            Program::compile(&context, &stms).run(&mut context).unwrap();
+
+            let Primitive::Table(table) = context.reg(&Symbol::from("t")).unwrap().clone() else { panic!("expected a table") };
+            let n    = Symbol::from("n");
+            let proc = Proc {
+                params:   Vec::default(),
+                captures: vec![(n, Capture::new(Primitive::Int(2i16)))],
+                body:     Arc::new(Decl::Expr(Expr::Op(Op::Mul(Val::Ref(PrimitiveId::Int, n), Box::new(Expr::Val(Val::Int(3i16))))))),
+            }; // let ..
+            let expr = Expr::Op(Op::Add(Val::Proc(Box::new(proc)), Box::new(Expr::Val(Val::Table(Box::new(table))))));
+            let stms = vec![Stm::RegLet(0usize, Symbol::from("x"), Decl::Expr(expr))];
+
+            let mut tree = context.clone();
+            Stm::run(&mut tree, &Decl::Closure(stms.clone())).unwrap();
+            Program::compile(&context, &stms).run(&mut context).unwrap();
+            assert_eq!(context.registers(), tree.registers());
+            assert!(matches!(context.registers()[&Symbol::from("x")], Primitive::Int(16i16 | 26i16)));
+        } // fn ..
+
+        #[test]
+        fn captures_are_compared_by_identity() {
+            let n    = Symbol::from("n");
+            let cell = Capture::new(Primitive::Int(2i16));
+            let proc = Proc { params: Vec::default(), captures: vec![(n, cell.clone())], body: Arc::new(Decl::Expr(Expr::Val(Val::Ref(PrimitiveId::Proc, n)))) };
+            cell.set(Primitive::Proc(proc.clone()));
+
+            let copy = Primitive::Proc(proc.clone());
+            assert_eq!(copy, Primitive::Proc(proc.clone()));
+            assert_ne!(copy, Primitive::Proc(Proc { captures: vec![(n, Capture::new(Primitive::Void))], ..proc.clone() }));
+            assert_eq!(HashSet::from([copy.clone(), copy.clone()]).len(), 1usize);
+            assert!(format!("{:?}", copy).contains("Capture("));
+
+            let json = Capture::shared(|| serde_json::to_string(&(copy.clone(), copy))).unwrap();
+            let (Primitive::Proc(a), Primitive::Proc(b)) = Capture::shared(|| serde_json::from_str::<(Primitive, Primitive)>(&json)).unwrap() else { panic!("expected procedures") };
+            assert_eq!(a.captures, b.captures);
+            assert!(matches!(a.captures[0usize].1.get(), Primitive::Proc(inner) if inner.captures == a.captures));
+        } // fn ..
+
+        #[test]
+        fn control_flow() {
+            agree("let [i] <- 0; let [s] <- 0; while | integer [i] < 10 |: set [i] <- integer [i] + 1; if | integer [i] = 3 | -> ...; set [s] <- integer [s] + 1d6; >>>>");
+            agree("let [s] <- 0; for [i] in | 10 ..< 0 by -3 |: set [s] <- integer [s] + integer [i]; >>>> let [t] <- 0; for each integer [x] in | list (1d4, 2, 3) |: set [t] <- integer [t] + integer [x]; >>>>");
+            agree("let [z]: if | 1d6 > 3 | -> 1; out -> 2; >>>> let [y] <- integer [z] * 2;");
+        } // fn ..
+
+        #[test]
+        fn procedures() {
+            agree("define [boom]: let [throw] <- 1d6; let [sum] <- integer [throw]; while | integer [throw] = 6 |: set [throw] <- 1d6; set [sum] <- integer [sum] + integer [throw]; >>>> out -> integer [sum]; >>>> let [i] <- 0; while | integer [i] < 30 |: tell -> integer [boom] + 3d6:>4; set [i] <- integer [i] + 1; >>>>");
+            agree("define [add]([a], [b]): out -> integer [a] + integer [b]; >>>> let [x] <- integer [add](1d6, 2); let [y] <- integer [add](integer [x], integer [add](1, 1));");
+            agree("let [n] <- 2; let [f] <- proc ([m]): out -> integer [m] * integer [n] + 1d4; >>>>; let [x] <- integer [f](3); let [y] <- proc [f] ;");
+            agree("define [roll]: out -> 1d20; >>>> let [x] <- proc [roll] + 1; let [y] <- 2 * proc [roll] - - proc [roll]; let [z] <- 0; if | proc [roll] > 10 |: set [z] <- proc: out -> 1d4; >>>> + 1; >>>>");
+            agree("table [t] | 1d6 |: 1..3 -> 1; 4..6 -> 2; >>>> let [x] <- table [t] * 10; let [y] <- 0; while | table [t] = 1 |: set [y] <- integer [y] + 1; >>>> assert -> proc: out -> 1d6 > 1; >>>>;");
+            agree("define [roll]: out -> 1d20; >>>> let [x] <- integer [roll] + 1; let [y] <- 2 d integer [roll]; let [l] <- list (proc [roll], 2); let [z] <- integer [l](0);");
+        } // fn ..
+
+        #[test]
+        fn scoped_definitions() {
+            let scripts = [
+                "define [f]: out -> 1; >>>> if | 1 = 1 |: define [f]: out -> 2; >>>> let [x] <- integer [f]; >>>> let [y] <- integer [f];",
+                "define [outer]: define [inner]([n]): out -> integer [n] * 2; >>>> out -> integer [inner](1d6); >>>> let [x] <- integer [outer];",
+                "define [g]: if | 1 = 1 |: define [h]: out -> 1d4; >>>> out -> integer [h] + 1; >>>> out -> 0; >>>> let [x] <- integer [g];",
+                "let [s] <- 0; for [i] in | 1..3 |: define [k]: out -> integer [i]; >>>> set [s] <- integer [s] + integer [k]; if | integer [i] = 2 | -> 1d6; >>>>",
+                "let [z]: define [h]: out -> 5; >>>> out -> integer [h]; >>>>",
+            ]; // let ..
+
+            for src in scripts {
+                agree(src);
+                let outcome = run(src, true);
+                assert_eq!(outcome.result, Ok(()), "`{}`", src);
+                for name in ["inner", "h", "k"] { assert!(!outcome.registers.contains_key(&Symbol::from(name)), "`{}` leaked out of `{}`", name, src) }
+            } // for ..
+
+            let outcome = run(scripts[0usize], true);
+            assert_eq!(outcome.registers[&Symbol::from("x")], shape(&Primitive::Int(2i16)));
+            assert_eq!(outcome.registers[&Symbol::from("y")], shape(&Primitive::Int(1i16)));
+        } // fn ..
+
+        #[test]
+        fn closures_keep_their_captures() {
+            let src = "define [counter]: let [n] <- 0; out -> proc: set [n] <- integer [n] + 1; out -> integer [n]; >>>>; >>>> let [c] <- proc [counter](); let [k] <- proc [c]; let [a] <- integer [c]; let [b] <- integer [c]; let [e] <- integer [k];";
+            agree(src);
+
+            let outcome = run(src, true);
+            for (name, value) in [("a", 1i16), ("b", 2i16), ("e", 3i16), ("n", 0i16)] {
+                assert_eq!(outcome.registers[&Symbol::from(name)], shape(&Primitive::Int(value)), "`{}`", name);
+            } // for ..
+
+            let mut context = SyntaxContext::default();
+            for (src, expected) in [(src, 3i16), ("let [e] <- integer [c];", 4i16), ("let [e] <- integer [k];", 5i16)] {
+                context.push(src).unwrap();
+                let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+                Program::compile(&context, &stms).run(&mut context).unwrap();
+                assert_eq!(context.registers()[&Symbol::from("e")], Primitive::Int(expected), "`{}`", src);
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn collections() {
+            agree("let [pool] <- list (1d6, 1d6, 1d6); append [pool] <- 1d8; set [pool](0) <- 6; remove [pool](1); let [n] <- length list [pool];");
+            agree("let [hero] <- record ([name] <- 'Aria', [str] <- 3d6, [stats] <- record ([hp] <- 10)); set [hero.str] <- integer [hero.str] + 2; set [hero.stats.hp] <- integer [hero.stats.hp] - 1d4; tell -> record [hero];");
+            agree("table [loot] | 1d20 |: 1..10 -> 'nothing'; 11..17 -> 1d4; 18..20: let [k] <- 1d2; out -> integer [k] * 10; >>>> >>>> tell -> text [loot]; tell -> text [loot](12); tell -> integer [loot](19);");
+        } // fn ..
+
+        #[test]
+        fn lists() {
+            let src  = "let [l] <- list (1, 2, 3); append [l] <- 4; set [l](0) <- 10; remove [l](1); let [n] <- length list [l]; let [x] <- integer [l](2); let [s] <- 0; for each integer [i] in | list [l] |: set [s] <- integer [s] + integer [i]; >>>> tell -> list [l];";
+            let list = Primitive::List(vec![Primitive::Int(10i16), Primitive::Int(3i16), Primitive::Int(4i16)]);
+
+            for vm in [false, true] {
+                let outcome = run(src, vm);
+                assert_eq!(outcome.registers[&Symbol::from("l")], shape(&list));
+                for (name, value) in [("n", 3i16), ("x", 4i16), ("s", 17i16)] {
+                    assert_eq!(outcome.registers[&Symbol::from(name)], shape(&Primitive::Int(value)), "`{}`", name);
+                } // for ..
+                assert!(!outcome.registers.contains_key(&Symbol::from("i")));
+                assert_eq!(outcome.told[0usize].0, list);
+
+                assert_eq!(run("let [l] <- list (1, 2); let [x] <- integer [l](2);", vm).result, Err(InterpreterErr::IndexOutOfBounds(2i16, 2usize).to_string()));
+                assert_eq!(run("let [l] <- list (1, 2); remove [l](-1);", vm).result, Err(InterpreterErr::IndexOutOfBounds(-1i16, 2usize).to_string()));
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn tables() {
+            let src = "table [t] | 1d6 |: 1..2 -> 'low'; 3..5 -> 10; 6..6: let [k] <- 2; out -> integer [k] * 10; >>>> >>>> let [a] <- text [t](1); let [b] <- integer [t](4); let [c] <- integer [t](6); let [r] <- text [t];";
+            for vm in [false, true] {
+                let outcome = run(src, vm);
+                for (name, value) in [("a", Primitive::Text(String::from("low"))), ("b", Primitive::Int(10i16)), ("c", Primitive::Int(20i16))] {
+                    assert_eq!(outcome.registers[&Symbol::from(name)], shape(&value), "`{}`", name);
+                } // for ..
+                assert!(outcome.registers.contains_key(&Symbol::from("r")));
+                assert_eq!(run("table [t] | 1d6 |: 1..6 -> 1; >>>> let [x] <- integer [t](7);", vm).result, Err(InterpreterErr::NoTableRow(7i16).to_string()));
+            } // for ..
+
+            for (rows, expected) in [("1..3 -> 1; 3..6 -> 2;", Err(3i16)), ("1..2 -> 1; 4..6 -> 2;", Ok(3i16)), ("2..6 -> 1;", Ok(1i16)), ("1..5 -> 1;", Ok(6i16))] {
+                let mut context = SyntaxContext::default();
+                context.push(&format!("table [t] | 1d6 |: {} >>>>", rows)).unwrap();
+                match (Stm::scan(&mut context, Vec::default(), WsToken::Eof), expected) {
+                    (Err(InterpreterErr::OverlappingRows(_, roll)), Err(expected)) => assert_eq!(roll, expected, "`{}`", rows),
+                    (Err(InterpreterErr::MissingRows(_, roll)), Ok(expected))      => assert_eq!(roll, expected, "`{}`", rows),
+                    (result, _)                                                    => panic!("`{}` gave {:?}", rows, result.map(|_| ())),
+                } // match ..
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn records() {
+            let src = "let [hero] <- record ([name] <- 'Aria', [str] <- 12, [stats] <- record ([hp] <- 10)); set [hero.str] <- integer [hero.str] + 2; set [hero.stats.hp] <- integer [hero.stats.hp] - 3; let [h] <- integer [hero.stats.hp]; tell -> record [hero];";
+            for vm in [false, true] {
+                let outcome = run(src, vm);
+                assert_eq!(outcome.registers[&Symbol::from("h")], shape(&Primitive::Int(7i16)));
+                assert_eq!(format!("{:?}", outcome.told[0usize].0), "Record({ name: Text(\"Aria\"), str: Int(14), stats: Record({ hp: Int(7) }) })");
+                assert_eq!(run("let [hero] <- record ([str] <- 12); let [x] <- integer [hero.dex];", vm).result, Err(InterpreterErr::UnknownField(Symbol::from("dex")).to_string()));
+            } // for ..
+
+            let mut context = SyntaxContext::default();
+            context.push("let [hero] <- record ([str] <- 12, [str] <- 13);").unwrap();
+            assert!(matches!(Stm::scan(&mut context, Vec::default(), WsToken::Eof), Err(InterpreterErr::DuplicateField(_, name)) if name == Symbol::from("str")));
+        } // fn ..
+
+        #[test]
+        fn counting_loops() {
+            let src = "let [i] <- 100; let [a] <- 0; for [i] in | 1..4 |: set [a] <- integer [a] + integer [i]; >>>> let [b] <- 0; for [k] in | 1 ..< 4 |: set [b] <- integer [b] + integer [k]; >>>> let [c] <- 0; for [k] in | 10..1 by -3 |: set [c] <- integer [c] + integer [k]; >>>> let [e] <- 0; for [k] in | 4..1 |: set [e] <- 1; >>>>";
+            for vm in [false, true] {
+                let outcome = run(src, vm);
+                for (name, value) in [("a", 10i16), ("b", 6i16), ("c", 22i16), ("e", 0i16), ("i", 100i16)] {
+                    assert_eq!(outcome.registers[&Symbol::from(name)], shape(&Primitive::Int(value)), "`{}`", name);
+                } // for ..
+                assert!(!outcome.registers.contains_key(&Symbol::from("k")));
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn macros_expand_their_arguments() {
+            let src = "let [str] <- 3; let [hero] <- record ([stats] <- record ([hp] <- 10)); define [twice]([n]): out -> integer [n] * 2; >>>> \
+                macro [attack]([bonus]) <- 1d20 + integer [bonus]; \
+                macro [hp]([c]) <- integer [c.stats.hp] + 1; \
+                macro [adder]([n]) <- proc ([x]): let [y] <- integer [x]; out -> integer [y] + integer [n]; >>>>; \
+                macro [same]([x]) <- proc ([x]): out -> integer [x]; >>>>; \
+                macro [apply]([f], [v]) <- integer [f](integer [v]) + 1; \
+                macro [show]([v]) <- text [v]; \
+                let [a] <- integer [attack](integer [str]); let [h] <- integer [hp](record [hero]); let [add] <- proc [adder](5); let [b] <- integer [add](1); \
+                let [s] <- proc [same](7); let [c] <- integer [s](2); let [e] <- integer [apply](proc [twice], 4); let [t] <- text [show](integer [str]);";
+            agree(src);
+
+            let outcome = run(src, true);
+            for (name, value) in [("h", Primitive::Int(11i16)), ("b", Primitive::Int(6i16)), ("c", Primitive::Int(2i16)), ("e", Primitive::Int(9i16)), ("t", Primitive::Text(String::from("3")))] {
+                assert_eq!(outcome.registers[&Symbol::from(name)], shape(&value), "`{}`", name);
+            } // for ..
+
+            for (src, expected) in [
+                ("macro [m]([x]) <- integer [x] + 1; let [y] <- integer [m]('a');", (PrimitiveId::Int, PrimitiveId::Text)),
+                ("macro [m]([c]) <- integer [c.hp]; let [y] <- integer [m](1);",    (PrimitiveId::Record, PrimitiveId::Int)),
+                ("macro [m]([f]) <- integer [f](1); let [y] <- integer [m](1 = 1);", (PrimitiveId::Proc, PrimitiveId::Bool)),
+            ] {
+                let mut context = SyntaxContext::default();
+                context.push(src).unwrap();
+                assert!(matches!(Stm::scan(&mut context, Vec::default(), WsToken::Eof), Err(InterpreterErr::MismatchedType(_, expected_id, found)) if expected_id == expected.0 && found == expected.1), "`{}`", src);
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn macros_are_defined_once_run_and_scoped_to_their_block() {
+            let m = Symbol::from("m");
+
+            for vm in [false, true] {
+                let mut context = SyntaxContext::default();
+                let eval        = |context: &mut SyntaxContext, src: &str| {
+                    context.push(src).unwrap();
+                    let stms = Stm::scan(context, Vec::default(), WsToken::Eof)?;
+                    match vm {
+                        true  => Program::compile(context, &stms).run(context).map(|_| ()),
+                        false => Stm::run(context, &Decl::Closure(stms)).map(|_| ()),
+                    } // match ..
+                }; // let ..
+
+                assert!(matches!(eval(&mut context, "if | 1 = 1 |: macro [m] <- 1; let [x] <- integer [m]; >>>> let [y] <- integer [m];"), Err(InterpreterErr::UninitReg(name)) if name == m));
+                assert!(context.find_macro(&m).is_none());
+
+                eval(&mut context, "define [f]: macro [m] <- 1; out -> integer [m]; >>>> let [z] <- integer [f];").unwrap();
+                assert!(context.find_macro(&m).is_none());
+
+                assert!(eval(&mut context, "macro [m] <- 1; let [x] <- ;").is_err());
+                context.discard();
+                assert!(context.find_macro(&m).is_none());
+
+                eval(&mut context, "macro [m]([n]) <- integer [n] * 2; let [y] <- integer [m](4);").unwrap();
+                eval(&mut context, "let [w] <- integer [m](5);").unwrap();
+                assert_eq!(context.registers()[&Symbol::from("w")], Primitive::Int(10i16));
+            } // for ..
+
+            let mut context = SyntaxContext::default();
+            context.push("macro [m] <- 2; let [x] <- boolean [nope];").unwrap();
+            let stms = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+            assert!(TypeChecker::new(&context).check(&stms).is_err());
+            context.discard();
+            assert!(context.find_macro(&m).is_none());
+        } // fn ..
+
+        #[test]
+        fn statistics() {
+            agree("assert mean | 200 | -> 3d6 = 10 within 1; assert never | 200 | -> 3d6 > 19;");
+            agree("assert -> 1d6 > 6;");
+        } // fn ..
+
+        #[test]
+        fn fractional_statistics() {
+            agree("assert mean | 400 | -> 1d2 = 1.5 within 0.25; assert mean | 10 | -> 1 = 1.05 within 0.06; assert mean | 10 | -> -1 = -1.5 within 0.5;");
+
+            for vm in [false, true] {
+                for (src, expected) in [("assert mean | 10 | -> 1 = 1.5 within 0.25;", (1.5f64, 0.25f64)), ("assert mean | 10 | -> 1 = 1.05 within 0.04;", (1.05f64, 0.04f64)), ("let [t] <- 3; assert mean | 10 | -> 1 = integer [t] within 1.5;", (3f64, 1.5f64))] {
+                    let mut context = SyntaxContext::default();
+                    context.push(src).unwrap();
+                    let stms   = Stm::scan(&mut context, Vec::default(), WsToken::Eof).unwrap();
+                    let result = match vm {
+                        true  => Program::compile(&context, &stms).run(&mut context).map(|_| ()),
+                        false => Stm::run(&mut context, &Decl::Closure(stms)).map(|_| ()),
+                    }; // let ..
+                    assert!(matches!(result, Err(InterpreterErr::MeanOutOfRange(_, _, _, target, tolerance)) if (target, tolerance) == expected), "`{}`", src);
+                } // for ..
+            } // for ..
+        } // fn ..
+
+        #[test]
+        fn statistics_need_runs() {
+            let src = "let [n] <- 0; assert never | integer [n] | -> 1d6 > 10;";
+            agree(src);
+            assert_eq!(run(src, true).result, Err(InterpreterErr::NoRuns(0i16).to_string()));
+
+            for src in ["assert mean | 0 | -> 3d6 = 10 within 1;", "assert never | -5 | -> 3d6 > 19;"] {
+                let mut context = SyntaxContext::default();
+                context.push(src).unwrap();
+                assert!(matches!(Stm::scan(&mut context, Vec::default(), WsToken::Eof), Err(InterpreterErr::NoRuns(..))), "`{}`", src);
+            } // for ..
+        } // fn ..
+    } // mod ..